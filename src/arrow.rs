@@ -0,0 +1,576 @@
+use crate::{
+    market_data::{
+        export::{FieldKind, FieldValue, Record, SYMBOL_COLUMN},
+        options::OptionSnapshots,
+    },
+    trading::{activities::TradeActivities, portfolio::PortfolioHistory},
+};
+use arrow_array::{
+    builder::{
+        Date32Builder, Float64Builder, Int64Builder, ListBuilder, StringBuilder,
+        TimestampNanosecondBuilder, TimestampSecondBuilder,
+    },
+    temporal_conversions::timestamp_ns_to_datetime,
+    types::Date32Type,
+    Array, ArrayRef, Float64Array, Int64Array, ListArray, RecordBatch, StringArray,
+    TimestampNanosecondArray,
+};
+use arrow_cast::parse::{string_to_timestamp_nanos, Parser};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use std::{collections::HashMap, sync::Arc};
+
+/// Conversion of API objects into an Arrow [`RecordBatch`]
+///
+/// Batches can be handed to Polars, DataFusion or any other Arrow consumer.
+pub trait ToRecordBatch {
+    fn to_record_batch(&self) -> Result<RecordBatch, ArrowError>;
+}
+
+fn timestamp_type(unit: TimeUnit) -> DataType {
+    DataType::Timestamp(unit, Some("UTC".into()))
+}
+
+fn parse_timestamp(value: &str) -> Result<i64, ArrowError> {
+    string_to_timestamp_nanos(value)
+}
+
+/// Format nanoseconds since the epoch the way Alpaca does, e.g. 2024-01-02T14:30:00Z
+fn format_timestamp(nanos: i64) -> Result<String, ArrowError> {
+    timestamp_ns_to_datetime(nanos)
+        .map(|datetime| datetime.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string())
+        .ok_or_else(|| ArrowError::ParseError(format!("Timestamp Out Of Range: {nanos}")))
+}
+
+fn record_schema<T: Record>() -> Schema {
+    let mut fields = vec![Field::new(SYMBOL_COLUMN, DataType::Utf8, false)];
+    for (name, kind) in T::fields() {
+        let data_type = match kind {
+            FieldKind::Text => DataType::Utf8,
+            FieldKind::Timestamp => timestamp_type(TimeUnit::Nanosecond),
+            FieldKind::Float => DataType::Float64,
+            FieldKind::Integer => DataType::Int64,
+            FieldKind::TextList => {
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)))
+            }
+        };
+        fields.push(Field::new(*name, data_type, false));
+    }
+    Schema::new(fields)
+}
+
+enum ColumnBuilder {
+    Text(StringBuilder),
+    Timestamp(TimestampNanosecondBuilder),
+    Float(Float64Builder),
+    Integer(Int64Builder),
+    TextList(ListBuilder<StringBuilder>),
+}
+
+impl ColumnBuilder {
+    fn new(kind: FieldKind) -> Self {
+        match kind {
+            FieldKind::Text => Self::Text(StringBuilder::new()),
+            FieldKind::Timestamp => {
+                Self::Timestamp(TimestampNanosecondBuilder::new().with_timezone("UTC"))
+            }
+            FieldKind::Float => Self::Float(Float64Builder::new()),
+            FieldKind::Integer => Self::Integer(Int64Builder::new()),
+            FieldKind::TextList => Self::TextList(ListBuilder::new(StringBuilder::new())),
+        }
+    }
+
+    fn append(&mut self, value: FieldValue) -> Result<(), ArrowError> {
+        match (self, value) {
+            (Self::Text(builder), FieldValue::Text(value)) => builder.append_value(value),
+            (Self::Timestamp(builder), FieldValue::Text(value)) => {
+                builder.append_value(parse_timestamp(&value)?)
+            }
+            (Self::Float(builder), FieldValue::Float(value)) => builder.append_value(value),
+            (Self::Integer(builder), FieldValue::Integer(value)) => builder.append_value(value),
+            (Self::TextList(builder), FieldValue::TextList(value)) => {
+                for item in value {
+                    builder.values().append_value(item);
+                }
+                builder.append(true);
+            }
+            (_, value) => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Unexpected Value: {value:?}"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            Self::Text(mut builder) => Arc::new(builder.finish()),
+            Self::Timestamp(mut builder) => Arc::new(builder.finish()),
+            Self::Float(mut builder) => Arc::new(builder.finish()),
+            Self::Integer(mut builder) => Arc::new(builder.finish()),
+            Self::TextList(mut builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Covers [`HistoricalBars`](crate::market_data::stocks::HistoricalBars),
+/// [`HistoricalTrades`](crate::market_data::stocks::HistoricalTrades),
+/// [`HistoricalQuotes`](crate::market_data::stocks::HistoricalQuotes) and the crypto and option equivalents
+impl<T: Record> ToRecordBatch for HashMap<String, Vec<T>> {
+    fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let mut symbols = StringBuilder::new();
+        let mut builders: Vec<ColumnBuilder> = T::fields()
+            .iter()
+            .map(|(_, kind)| ColumnBuilder::new(*kind))
+            .collect();
+
+        let mut keys: Vec<&String> = self.keys().collect();
+        keys.sort();
+
+        for symbol in keys {
+            for record in &self[symbol] {
+                symbols.append_value(symbol);
+                for (builder, value) in builders.iter_mut().zip(record.to_values()) {
+                    builder.append(value)?;
+                }
+            }
+        }
+
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(symbols.finish())];
+        columns.extend(builders.into_iter().map(ColumnBuilder::finish));
+
+        RecordBatch::try_new(Arc::new(record_schema::<T>()), columns)
+    }
+}
+
+/// Read a batch produced by [`ToRecordBatch`] back into a symbol keyed data map
+pub fn records_from_batch<T: Record>(
+    batch: &RecordBatch,
+) -> Result<HashMap<String, Vec<T>>, ArrowError> {
+    let symbols = column::<StringArray>(batch, SYMBOL_COLUMN)?;
+
+    let mut data: HashMap<String, Vec<T>> = HashMap::new();
+    for row in 0..batch.num_rows() {
+        let mut values = Vec::with_capacity(T::fields().len());
+        for (name, kind) in T::fields() {
+            values.push(read_value(batch, name, *kind, row)?);
+        }
+
+        let record = T::from_values(values).map_err(|e| ArrowError::ParseError(e.to_string()))?;
+        data.entry(symbols.value(row).to_string())
+            .or_default()
+            .push(record);
+    }
+
+    Ok(data)
+}
+
+fn column<'a, A: Array + 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a A, ArrowError> {
+    batch
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<A>())
+        .ok_or_else(|| ArrowError::SchemaError(format!("Missing Or Mistyped Column: {name}")))
+}
+
+fn read_value(
+    batch: &RecordBatch,
+    name: &str,
+    kind: FieldKind,
+    row: usize,
+) -> Result<FieldValue, ArrowError> {
+    Ok(match kind {
+        FieldKind::Text => {
+            FieldValue::Text(column::<StringArray>(batch, name)?.value(row).to_string())
+        }
+        FieldKind::Timestamp => {
+            let nanos = column::<TimestampNanosecondArray>(batch, name)?.value(row);
+            FieldValue::Text(format_timestamp(nanos)?)
+        }
+        FieldKind::Float => FieldValue::Float(column::<Float64Array>(batch, name)?.value(row)),
+        FieldKind::Integer => FieldValue::Integer(column::<Int64Array>(batch, name)?.value(row)),
+        FieldKind::TextList => {
+            let items = column::<ListArray>(batch, name)?.value(row);
+            let items = items
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| ArrowError::SchemaError(format!("Mistyped Column: {name}")))?;
+            FieldValue::TextList(items.iter().flatten().map(str::to_string).collect())
+        }
+    })
+}
+
+fn parse_optional_timestamp(value: Option<&str>) -> Result<Option<i64>, ArrowError> {
+    value.map(parse_timestamp).transpose()
+}
+
+fn parse_optional_float(value: Option<&str>) -> Option<f64> {
+    value.and_then(|value| value.parse().ok())
+}
+
+impl ToRecordBatch for OptionSnapshots {
+    fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let nanos = || TimestampNanosecondBuilder::new().with_timezone("UTC");
+        let mut symbols = StringBuilder::new();
+        let mut implied_volatility = Float64Builder::new();
+        let mut delta = Float64Builder::new();
+        let mut gamma = Float64Builder::new();
+        let mut rho = Float64Builder::new();
+        let mut theta = Float64Builder::new();
+        let mut vega = Float64Builder::new();
+        let mut quote_time = nanos();
+        let mut bid_price = Float64Builder::new();
+        let mut bid_size = Int64Builder::new();
+        let mut ask_price = Float64Builder::new();
+        let mut ask_size = Int64Builder::new();
+        let mut trade_time = nanos();
+        let mut trade_price = Float64Builder::new();
+        let mut trade_size = Int64Builder::new();
+
+        let mut keys: Vec<&String> = self.keys().collect();
+        keys.sort();
+
+        for symbol in keys {
+            let snapshot = &self[symbol];
+            symbols.append_value(symbol);
+            implied_volatility.append_option(snapshot.implied_volatility);
+
+            let greeks = snapshot.greeks.as_ref();
+            delta.append_option(greeks.map(|g| g.delta));
+            gamma.append_option(greeks.map(|g| g.gamma));
+            rho.append_option(greeks.map(|g| g.rho));
+            theta.append_option(greeks.map(|g| g.theta));
+            vega.append_option(greeks.map(|g| g.vega));
+
+            let quote = snapshot.latest_quote.as_ref();
+            quote_time.append_option(parse_optional_timestamp(quote.map(|q| q.t.as_str()))?);
+            bid_price.append_option(quote.map(|q| q.bp as f64));
+            bid_size.append_option(quote.map(|q| q.bs as i64));
+            ask_price.append_option(quote.map(|q| q.ap as f64));
+            ask_size.append_option(quote.map(|q| q.r#as as i64));
+
+            let trade = snapshot.latest_trade.as_ref();
+            trade_time.append_option(parse_optional_timestamp(trade.map(|t| t.t.as_str()))?);
+            trade_price.append_option(trade.map(|t| t.p));
+            trade_size.append_option(trade.map(|t| t.s as i64));
+        }
+
+        let float = |name: &str| Field::new(name, DataType::Float64, true);
+        let integer = |name: &str| Field::new(name, DataType::Int64, true);
+        let time = |name: &str| Field::new(name, timestamp_type(TimeUnit::Nanosecond), true);
+        let schema = Schema::new(vec![
+            Field::new(SYMBOL_COLUMN, DataType::Utf8, false),
+            float("implied_volatility"),
+            float("delta"),
+            float("gamma"),
+            float("rho"),
+            float("theta"),
+            float("vega"),
+            time("quote_time"),
+            float("bid_price"),
+            integer("bid_size"),
+            float("ask_price"),
+            integer("ask_size"),
+            time("trade_time"),
+            float("trade_price"),
+            integer("trade_size"),
+        ]);
+
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(symbols.finish()),
+                Arc::new(implied_volatility.finish()),
+                Arc::new(delta.finish()),
+                Arc::new(gamma.finish()),
+                Arc::new(rho.finish()),
+                Arc::new(theta.finish()),
+                Arc::new(vega.finish()),
+                Arc::new(quote_time.finish()),
+                Arc::new(bid_price.finish()),
+                Arc::new(bid_size.finish()),
+                Arc::new(ask_price.finish()),
+                Arc::new(ask_size.finish()),
+                Arc::new(trade_time.finish()),
+                Arc::new(trade_price.finish()),
+                Arc::new(trade_size.finish()),
+            ],
+        )
+    }
+}
+
+impl ToRecordBatch for PortfolioHistory {
+    fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let mut timestamp = TimestampSecondBuilder::new().with_timezone("UTC");
+        timestamp.append_slice(&self.timestamp);
+
+        let schema = Schema::new(vec![
+            Field::new("timestamp", timestamp_type(TimeUnit::Second), false),
+            Field::new("equity", DataType::Float64, false),
+            Field::new("profit_loss", DataType::Float64, false),
+            Field::new("profit_loss_pct", DataType::Float64, false),
+        ]);
+
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(timestamp.finish()),
+                Arc::new(Float64Array::from(self.equity.clone())),
+                Arc::new(Float64Array::from(self.profit_loss.clone())),
+                Arc::new(Float64Array::from(self.profit_loss_pct.clone())),
+            ],
+        )
+    }
+}
+
+impl ToRecordBatch for TradeActivities {
+    fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let text_columns = [
+            "activity_type",
+            "id",
+            "symbol",
+            "side",
+            "order_id",
+            "type",
+            "order_status",
+            "group_id",
+            "status",
+        ];
+        let float_columns = [
+            "qty",
+            "price",
+            "cum_qty",
+            "leaves_qty",
+            "net_amount",
+            "per_share_amount",
+        ];
+
+        let mut text: Vec<StringBuilder> =
+            text_columns.iter().map(|_| StringBuilder::new()).collect();
+        let mut floats: Vec<Float64Builder> = float_columns
+            .iter()
+            .map(|_| Float64Builder::new())
+            .collect();
+        let mut transaction_time = TimestampNanosecondBuilder::new().with_timezone("UTC");
+        let mut date = Date32Builder::new();
+
+        for activity in self {
+            let text_values = [
+                activity.activity_type.as_deref(),
+                Some(activity.id.as_str()),
+                activity.symbol.as_deref(),
+                activity.side.as_deref(),
+                activity.order_id.as_deref(),
+                activity.r#type.as_deref(),
+                activity.order_status.as_deref(),
+                activity.group_id.as_deref(),
+                activity.status.as_deref(),
+            ];
+            for (builder, value) in text.iter_mut().zip(text_values) {
+                builder.append_option(value);
+            }
+
+            let float_values = [
+                activity.qty.as_deref(),
+                activity.price.as_deref(),
+                activity.cum_qty.as_deref(),
+                activity.leaves_qty.as_deref(),
+                activity.net_amount.as_deref(),
+                activity.per_share_amount.as_deref(),
+            ];
+            for (builder, value) in floats.iter_mut().zip(float_values) {
+                builder.append_option(parse_optional_float(value));
+            }
+
+            transaction_time.append_option(parse_optional_timestamp(
+                activity.transaction_time.as_deref(),
+            )?);
+            date.append_option(activity.date.as_deref().and_then(Date32Type::parse));
+        }
+
+        let mut fields = Vec::new();
+        let mut columns: Vec<ArrayRef> = Vec::new();
+        for (name, mut builder) in text_columns.into_iter().zip(text) {
+            fields.push(Field::new(name, DataType::Utf8, name != "id"));
+            columns.push(Arc::new(builder.finish()));
+        }
+        for (name, mut builder) in float_columns.into_iter().zip(floats) {
+            fields.push(Field::new(name, DataType::Float64, true));
+            columns.push(Arc::new(builder.finish()));
+        }
+        fields.push(Field::new(
+            "transaction_time",
+            timestamp_type(TimeUnit::Nanosecond),
+            true,
+        ));
+        columns.push(Arc::new(transaction_time.finish()));
+        fields.push(Field::new("date", DataType::Date32, true));
+        columns.push(Arc::new(date.finish()));
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::{
+        options::{Greeks, OptionQuote, OptionSnapshot},
+        stocks::{HistoricalBars, StockBar},
+    };
+    use crate::trading::activities::TradeActivity;
+    use arrow_array::{Date32Array, TimestampSecondArray};
+
+    fn bars() -> HistoricalBars {
+        let mut bars = HashMap::new();
+        bars.insert(
+            "AAPL".to_string(),
+            vec![StockBar {
+                t: "2022-02-01T05:00:00Z".to_string(),
+                o: 174.0,
+                h: 174.78,
+                l: 172.36,
+                c: 174.54,
+                v: 1094581.0,
+                n: 10621,
+                vw: 173.47993,
+            }],
+        );
+        bars
+    }
+
+    #[test]
+    fn test_bars_to_record_batch() {
+        let batch = bars().to_record_batch().unwrap();
+
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(
+            batch.schema().field_with_name("t").unwrap().data_type(),
+            &timestamp_type(TimeUnit::Nanosecond)
+        );
+
+        let t = column::<TimestampNanosecondArray>(&batch, "t").unwrap();
+        assert_eq!(t.value(0), 1_643_691_600_000_000_000);
+
+        let close = column::<Float64Array>(&batch, "c").unwrap();
+        assert_eq!(close.value(0), 174.54);
+    }
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let batch = bars().to_record_batch().unwrap();
+        let restored: HistoricalBars = records_from_batch(&batch).unwrap();
+        assert_eq!(restored["AAPL"][0].t, "2022-02-01T05:00:00Z");
+        assert_eq!(restored["AAPL"][0].n, 10621);
+    }
+
+    #[test]
+    fn test_fractional_timestamps_keep_precision() {
+        let nanos = parse_timestamp("2024-09-20T14:28:58.123456789Z").unwrap();
+        assert_eq!(
+            format_timestamp(nanos).unwrap(),
+            "2024-09-20T14:28:58.123456789Z"
+        );
+    }
+
+    #[test]
+    fn test_option_snapshots_to_record_batch() {
+        let mut snapshots = HashMap::new();
+        snapshots.insert(
+            "AAPL241220C00300000".to_string(),
+            OptionSnapshot {
+                greeks: Some(Greeks {
+                    delta: 0.5,
+                    gamma: 0.02,
+                    rho: 0.1,
+                    theta: -0.05,
+                    vega: 0.2,
+                }),
+                latest_quote: Some(OptionQuote {
+                    t: "2024-09-20T14:28:58Z".to_string(),
+                    ax: "C".to_string(),
+                    ap: 1.25,
+                    r#as: 10,
+                    bx: "C".to_string(),
+                    bp: 1.2,
+                    bs: 5,
+                    c: "A".to_string(),
+                }),
+                latest_trade: None,
+                implied_volatility: None,
+            },
+        );
+
+        let batch = snapshots.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(
+            column::<Float64Array>(&batch, "delta").unwrap().value(0),
+            0.5
+        );
+        assert!(column::<Float64Array>(&batch, "implied_volatility")
+            .unwrap()
+            .is_null(0));
+        assert!(column::<Float64Array>(&batch, "trade_price")
+            .unwrap()
+            .is_null(0));
+    }
+
+    #[test]
+    fn test_portfolio_history_to_record_batch() {
+        let history = PortfolioHistory {
+            timestamp: vec![1_700_000_000, 1_700_086_400],
+            equity: vec![100_000.0, 100_500.0],
+            profit_loss: vec![0.0, 500.0],
+            profit_loss_pct: vec![0.0, 0.005],
+            base_value: 100_000.0,
+            base_value_asof: None,
+            timeframe: "1D".to_string(),
+            cashflow: None,
+        };
+
+        let batch = history.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let timestamp = column::<TimestampSecondArray>(&batch, "timestamp").unwrap();
+        assert_eq!(timestamp.value(1), 1_700_086_400);
+    }
+
+    #[test]
+    fn test_trade_activities_to_record_batch() {
+        let activity: TradeActivity = serde_json::from_str(
+            r#"{
+                "activity_type": "FILL",
+                "id": "20240920093000000::abc",
+                "cum_qty": "1",
+                "leaves_qty": "0",
+                "price": "228.87",
+                "qty": "1",
+                "side": "buy",
+                "symbol": "AAPL",
+                "transaction_time": "2024-09-20T13:30:00.123Z",
+                "order_id": "abc",
+                "type": "fill",
+                "order_status": "filled"
+            }"#,
+        )
+        .unwrap();
+        let dividend: TradeActivity = serde_json::from_str(
+            r#"{"activity_type": "DIV", "id": "def", "date": "2024-09-20", "net_amount": "1.5"}"#,
+        )
+        .unwrap();
+
+        let batch = vec![activity, dividend].to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            column::<Float64Array>(&batch, "price").unwrap().value(0),
+            228.87
+        );
+        assert!(
+            column::<TimestampNanosecondArray>(&batch, "transaction_time")
+                .unwrap()
+                .is_null(1)
+        );
+        assert_eq!(
+            column::<Date32Array>(&batch, "date").unwrap().value(1),
+            19986
+        );
+    }
+}
@@ -0,0 +1,163 @@
+mod momentum;
+pub use momentum::*;
+
+mod moving_average;
+pub use moving_average::*;
+
+mod trend;
+pub use trend::*;
+
+mod volatility;
+pub use volatility::*;
+
+mod volume;
+pub use volume::*;
+
+use crate::{market_data::stocks::StockBar, StreamBar};
+
+/// OHLCV access shared by historical and streamed bars
+pub trait Candle {
+    fn open(&self) -> f64;
+    fn high(&self) -> f64;
+    fn low(&self) -> f64;
+    fn close(&self) -> f64;
+    fn volume(&self) -> f64;
+
+    /// Average of high, low and close
+    fn typical_price(&self) -> f64 {
+        (self.high() + self.low() + self.close()) / 3.0
+    }
+}
+
+impl Candle for StockBar {
+    fn open(&self) -> f64 {
+        self.o as f64
+    }
+
+    fn high(&self) -> f64 {
+        self.h as f64
+    }
+
+    fn low(&self) -> f64 {
+        self.l as f64
+    }
+
+    fn close(&self) -> f64 {
+        self.c as f64
+    }
+
+    fn volume(&self) -> f64 {
+        self.v as f64
+    }
+}
+
+impl Candle for StreamBar {
+    fn open(&self) -> f64 {
+        self.o as f64
+    }
+
+    fn high(&self) -> f64 {
+        self.h as f64
+    }
+
+    fn low(&self) -> f64 {
+        self.l as f64
+    }
+
+    fn close(&self) -> f64 {
+        self.c as f64
+    }
+
+    fn volume(&self) -> f64 {
+        self.v as f64
+    }
+}
+
+/// A streaming indicator fed one candle at a time
+///
+/// `next` returns `None` until enough candles have been seen to produce a value.
+pub trait Indicator {
+    type Output;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<Self::Output>;
+
+    /// Forget all candles seen so far
+    fn reset(&mut self);
+
+    /// Run the indicator over a whole series, the output lines up with the input
+    fn calculate<C: Candle>(&mut self, candles: &[C]) -> Vec<Option<Self::Output>> {
+        candles.iter().map(|candle| self.next(candle)).collect()
+    }
+}
+
+pub fn sma<C: Candle>(candles: &[C], period: usize) -> Vec<Option<f64>> {
+    Sma::new(period).calculate(candles)
+}
+
+pub fn ema<C: Candle>(candles: &[C], period: usize) -> Vec<Option<f64>> {
+    Ema::new(period).calculate(candles)
+}
+
+pub fn rsi<C: Candle>(candles: &[C], period: usize) -> Vec<Option<f64>> {
+    Rsi::new(period).calculate(candles)
+}
+
+pub fn macd<C: Candle>(candles: &[C]) -> Vec<Option<MacdValue>> {
+    Macd::default().calculate(candles)
+}
+
+pub fn bollinger_bands<C: Candle>(
+    candles: &[C],
+    period: usize,
+    deviations: f64,
+) -> Vec<Option<BollingerValue>> {
+    BollingerBands::new(period, deviations).calculate(candles)
+}
+
+pub fn atr<C: Candle>(candles: &[C], period: usize) -> Vec<Option<f64>> {
+    Atr::new(period).calculate(candles)
+}
+
+pub fn vwap<C: Candle>(candles: &[C]) -> Vec<Option<f64>> {
+    Vwap::new().calculate(candles)
+}
+
+pub fn obv<C: Candle>(candles: &[C]) -> Vec<Option<f64>> {
+    Obv::new().calculate(candles)
+}
+
+pub fn stochastic<C: Candle>(
+    candles: &[C],
+    k_period: usize,
+    d_period: usize,
+) -> Vec<Option<StochasticValue>> {
+    Stochastic::new(k_period, d_period).calculate(candles)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::market_data::stocks::StockBar;
+
+    /// Bars with the given closes, high and low one above and below
+    pub(crate) fn bars(closes: &[f32]) -> Vec<StockBar> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| StockBar {
+                t: format!("2024-07-01T13:{:02}:00Z", 30 + i),
+                o: *close,
+                h: close + 1.0,
+                l: close - 1.0,
+                c: *close,
+                v: 100.0,
+                n: 1,
+                vw: *close,
+            })
+            .collect()
+    }
+
+    pub(crate) fn approx(value: Option<f64>, expected: f64) {
+        let value = value.expect("Indicator has a value");
+        assert!((value - expected).abs() < 1e-6, "{value} is not {expected}");
+    }
+}
@@ -0,0 +1,226 @@
+use super::{Candle, Ema, Indicator, Sma};
+use std::collections::VecDeque;
+
+/// Relative strength index using Wilder's smoothing
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    seen: usize,
+    average_gain: f64,
+    average_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous: None,
+            seen: 0,
+            average_gain: 0.0,
+            average_loss: 0.0,
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        let close = candle.close();
+        let previous = self.previous.replace(close)?;
+        let change = close - previous;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f64;
+
+        self.seen += 1;
+        if self.seen <= self.period {
+            self.average_gain += gain / period;
+            self.average_loss += loss / period;
+            if self.seen < self.period {
+                return None;
+            }
+        } else {
+            self.average_gain = (self.average_gain * (period - 1.0) + gain) / period;
+            self.average_loss = (self.average_loss * (period - 1.0) + loss) / period;
+        }
+
+        if self.average_loss == 0.0 {
+            return Some(if self.average_gain == 0.0 {
+                50.0
+            } else {
+                100.0
+            });
+        }
+        Some(100.0 - 100.0 / (1.0 + self.average_gain / self.average_loss))
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Moving average convergence divergence, 12/26/9 by default
+#[derive(Debug, Clone)]
+pub struct Macd {
+    periods: (usize, usize, usize),
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            periods: (fast, slow, signal),
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+}
+
+impl Default for Macd {
+    fn default() -> Self {
+        Self::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<MacdValue> {
+        let fast = self.fast.update(candle.close());
+        let slow = self.slow.update(candle.close());
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+
+    fn reset(&mut self) {
+        let (fast, slow, signal) = self.periods;
+        *self = Self::new(fast, slow, signal);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticValue {
+    /// %K, where the close sits in the recent high to low range
+    pub k: f64,
+    /// %D, the moving average of %K, once enough %K values exist
+    pub d: Option<f64>,
+}
+
+/// Stochastic oscillator
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    k_period: usize,
+    window: VecDeque<(f64, f64)>,
+    d: Sma,
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        let k_period = k_period.max(1);
+        Self {
+            k_period,
+            window: VecDeque::with_capacity(k_period),
+            d: Sma::new(d_period),
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticValue;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<StochasticValue> {
+        self.window.push_back((candle.high(), candle.low()));
+        if self.window.len() > self.k_period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.k_period {
+            return None;
+        }
+
+        let high = self
+            .window
+            .iter()
+            .map(|(high, _)| *high)
+            .fold(f64::MIN, f64::max);
+        let low = self
+            .window
+            .iter()
+            .map(|(_, low)| *low)
+            .fold(f64::MAX, f64::min);
+        let k = if high > low {
+            100.0 * (candle.close() - low) / (high - low)
+        } else {
+            50.0
+        };
+        Some(StochasticValue {
+            k,
+            d: self.d.update(k),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.d.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::tests::{approx, bars};
+
+    #[test]
+    fn test_rsi() {
+        let values = Rsi::new(2).calculate(&bars(&[10.0, 11.0, 10.0, 12.0]));
+        assert_eq!(values[1], None);
+        // Gains 1 and 0, losses 0 and 1 average out even
+        approx(values[2], 50.0);
+        // Wilder smoothing: gain (0.5 + 2) / 2 and loss (0.5 + 0) / 2
+        approx(values[3], 100.0 - 100.0 / (1.0 + 1.25 / 0.25));
+
+        let rising = Rsi::new(2).calculate(&bars(&[1.0, 2.0, 3.0]));
+        approx(rising[2], 100.0);
+    }
+
+    #[test]
+    fn test_macd() {
+        let closes: Vec<f32> = (1..=40).map(|i| i as f32).collect();
+        let values = Macd::default().calculate(&bars(&closes));
+        assert!(values[32].is_none());
+
+        // A straight line keeps the EMAs a fixed distance apart
+        let value = values[33].unwrap();
+        approx(Some(value.macd), 7.0);
+        approx(Some(value.histogram), 0.0);
+    }
+
+    #[test]
+    fn test_stochastic() {
+        let values = Stochastic::new(3, 2).calculate(&bars(&[1.0, 2.0, 3.0, 2.0]));
+        assert_eq!(values[1], None);
+        // Range is 0 to 4 with a close of 3
+        let value = values[2].unwrap();
+        approx(Some(value.k), 75.0);
+        assert_eq!(value.d, None);
+        // Range is 1 to 4 with a close of 2
+        let value = values[3].unwrap();
+        approx(Some(value.k), 100.0 / 3.0);
+        approx(value.d, (75.0 + 100.0 / 3.0) / 2.0);
+    }
+}
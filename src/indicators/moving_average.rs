@@ -0,0 +1,111 @@
+use super::{Candle, Indicator};
+use std::collections::VecDeque;
+
+/// Simple moving average of closes
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    /// Feed a raw value rather than a candle's close
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        self.update(candle.close())
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+    }
+}
+
+/// Exponential moving average of closes, seeded with the SMA of the first period
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    /// Feed a raw value rather than a candle's close
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        self.update(candle.close())
+    }
+
+    fn reset(&mut self) {
+        self.seed.reset();
+        self.value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::tests::{approx, bars};
+
+    #[test]
+    fn test_sma() {
+        let values = Sma::new(3).calculate(&bars(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+        assert_eq!(values[1], None);
+        approx(values[2], 2.0);
+        approx(values[4], 4.0);
+    }
+
+    #[test]
+    fn test_ema() {
+        let values = Ema::new(3).calculate(&bars(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+        assert_eq!(values[1], None);
+        approx(values[2], 2.0);
+        approx(values[3], 3.0);
+        approx(values[4], 4.0);
+
+        let values = Ema::new(3).calculate(&bars(&[2.0, 2.0, 2.0, 8.0]));
+        approx(values[3], 5.0);
+    }
+}
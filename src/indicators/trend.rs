@@ -0,0 +1,77 @@
+use super::{Candle, Ema, Indicator};
+use crate::Trend;
+
+/// Classifies trend from a fast and slow EMA crossover of closes
+///
+/// Bullish while the fast EMA is above the slow one, bearish while below, and no
+/// value while they are level or not yet warmed up.
+#[derive(Debug, Clone)]
+pub struct TrendClassifier {
+    periods: (usize, usize),
+    fast: Ema,
+    slow: Ema,
+}
+
+impl TrendClassifier {
+    pub fn new(fast: usize, slow: usize) -> Self {
+        Self {
+            periods: (fast, slow),
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+        }
+    }
+}
+
+impl Default for TrendClassifier {
+    fn default() -> Self {
+        Self::new(20, 50)
+    }
+}
+
+impl Indicator for TrendClassifier {
+    type Output = Trend;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<Trend> {
+        let fast = self.fast.update(candle.close());
+        let slow = self.slow.update(candle.close());
+        let (fast, slow) = (fast?, slow?);
+
+        if fast > slow {
+            Some(Trend::Bullish)
+        } else if fast < slow {
+            Some(Trend::Bearish)
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        let (fast, slow) = self.periods;
+        *self = Self::new(fast, slow);
+    }
+}
+
+/// Trend at the end of a series using a fast and slow EMA crossover
+pub fn classify_trend<C: Candle>(candles: &[C], fast: usize, slow: usize) -> Option<Trend> {
+    TrendClassifier::new(fast, slow)
+        .calculate(candles)
+        .pop()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::tests::bars;
+
+    #[test]
+    fn test_classify_trend() {
+        let rising: Vec<f32> = (1..=10).map(|i| i as f32).collect();
+        let falling: Vec<f32> = rising.iter().rev().copied().collect();
+
+        assert_eq!(classify_trend(&bars(&rising), 3, 6), Some(Trend::Bullish));
+        assert_eq!(classify_trend(&bars(&falling), 3, 6), Some(Trend::Bearish));
+        assert_eq!(classify_trend(&bars(&rising[..4]), 3, 6), None);
+        assert_eq!(classify_trend(&bars(&[5.0; 10]), 3, 6), None);
+    }
+}
@@ -0,0 +1,141 @@
+use super::{Candle, Indicator};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Bollinger bands, an SMA of closes with bands a number of standard deviations away
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    period: usize,
+    deviations: f64,
+    window: VecDeque<f64>,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, deviations: f64) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            deviations,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+}
+
+impl Default for BollingerBands {
+    fn default() -> Self {
+        Self::new(20, 2.0)
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = BollingerValue;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<BollingerValue> {
+        self.window.push_back(candle.close());
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let period = self.period as f64;
+        let middle = self.window.iter().sum::<f64>() / period;
+        let variance = self
+            .window
+            .iter()
+            .map(|close| (close - middle).powi(2))
+            .sum::<f64>()
+            / period;
+        let width = self.deviations * variance.sqrt();
+        Some(BollingerValue {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// Average true range using Wilder's smoothing
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f64>,
+    seen: usize,
+    value: f64,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous_close: None,
+            seen: 0,
+            value: 0.0,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        let range = candle.high() - candle.low();
+        let true_range = match self.previous_close.replace(candle.close()) {
+            Some(close) => range
+                .max((candle.high() - close).abs())
+                .max((candle.low() - close).abs()),
+            None => range,
+        };
+        let period = self.period as f64;
+
+        self.seen += 1;
+        if self.seen <= self.period {
+            self.value += true_range / period;
+            (self.seen == self.period).then_some(self.value)
+        } else {
+            self.value = (self.value * (period - 1.0) + true_range) / period;
+            Some(self.value)
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::tests::{approx, bars};
+
+    #[test]
+    fn test_bollinger_bands() {
+        let values = BollingerBands::new(4, 2.0).calculate(&bars(&[2.0, 4.0, 4.0, 6.0]));
+        assert_eq!(values[2], None);
+        // Mean of 4 with a population standard deviation of sqrt(2)
+        let value = values[3].unwrap();
+        approx(Some(value.middle), 4.0);
+        approx(Some(value.upper), 4.0 + 2.0 * 2f64.sqrt());
+        approx(Some(value.lower), 4.0 - 2.0 * 2f64.sqrt());
+    }
+
+    #[test]
+    fn test_atr() {
+        let values = Atr::new(2).calculate(&bars(&[10.0, 10.0, 14.0]));
+        // Ranges are 2 and 2, then the gap up gives a true range of 5
+        assert_eq!(values[0], None);
+        approx(values[1], 2.0);
+        approx(values[2], 3.5);
+    }
+}
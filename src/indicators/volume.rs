@@ -0,0 +1,85 @@
+use super::{Candle, Indicator};
+
+/// Volume weighted average price of the typical price, call `reset` at each session
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    notional: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        self.notional += candle.typical_price() * candle.volume();
+        self.volume += candle.volume();
+        (self.volume > 0.0).then(|| self.notional / self.volume)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// On balance volume, starting from zero at the first candle
+#[derive(Debug, Clone, Default)]
+pub struct Obv {
+    previous_close: Option<f64>,
+    value: f64,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Indicator for Obv {
+    type Output = f64;
+
+    fn next<C: Candle>(&mut self, candle: &C) -> Option<f64> {
+        let close = candle.close();
+        if let Some(previous) = self.previous_close.replace(close) {
+            if close > previous {
+                self.value += candle.volume();
+            } else if close < previous {
+                self.value -= candle.volume();
+            }
+        }
+        Some(self.value)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::tests::{approx, bars};
+
+    #[test]
+    fn test_vwap() {
+        let mut candles = bars(&[10.0, 20.0]);
+        candles[1].v = 300.0;
+        let values = Vwap::new().calculate(&candles);
+        approx(values[0], 10.0);
+        approx(values[1], 17.5);
+    }
+
+    #[test]
+    fn test_obv() {
+        let values = Obv::new().calculate(&bars(&[10.0, 11.0, 11.0, 9.0]));
+        approx(values[0], 0.0);
+        approx(values[1], 100.0);
+        approx(values[2], 100.0);
+        approx(values[3], 0.0);
+    }
+}
@@ -4,8 +4,18 @@ use ureq::Request;
 pub mod market_data;
 pub mod trading;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+
+pub mod indicators;
+pub mod patterns;
+pub mod payoff;
+
+mod occ;
+pub use occ::{OccSymbol, OptionType};
+
 mod timeframe;
-pub use timeframe::TimeFrame;
+pub use timeframe::{TimeFrame, TimeFrameUnit};
 
 mod trend;
 pub use trend::Trend;
@@ -14,6 +24,7 @@ mod stream;
 pub use stream::*;
 
 mod serde;
+mod time;
 
 fn request(method: &str, address: &str) -> Request {
     dotenv().ok();
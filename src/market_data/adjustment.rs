@@ -0,0 +1,301 @@
+//! Corporate action adjustment of raw stock bars, so cached unadjusted data can be
+//! re-adjusted as of any date without downloading it again.
+
+use super::{
+    corporate_actions::CorporateActions,
+    stocks::{HistoricalBars, StockBar},
+};
+use crate::time::{new_york_offset, parse_date, parse_timestamp};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+};
+
+/// Which corporate actions bar prices and volumes are adjusted for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjustment {
+    Raw,
+    /// Splits and stock dividends
+    Split,
+    /// Cash dividends
+    Dividend,
+    All,
+}
+
+impl Adjustment {
+    fn splits(&self) -> bool {
+        matches!(self, Adjustment::Split | Adjustment::All)
+    }
+
+    fn dividends(&self) -> bool {
+        matches!(self, Adjustment::Dividend | Adjustment::All)
+    }
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let adjustment = match self {
+            Adjustment::Raw => "raw",
+            Adjustment::Split => "split",
+            Adjustment::Dividend => "dividend",
+            Adjustment::All => "all",
+        };
+        write!(f, "{adjustment}")
+    }
+}
+
+/// Multipliers applied to every bar before the ex date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdjustmentFactor {
+    pub ex_date: NaiveDate,
+    pub price: f64,
+    pub volume: f64,
+}
+
+/// Trading date of a bar in New York
+fn bar_date(bar: &StockBar) -> Result<NaiveDate, Error> {
+    let timestamp = parse_timestamp(&bar.t)?;
+    Ok(timestamp
+        .with_timezone(&new_york_offset(timestamp.date_naive()))
+        .date_naive())
+}
+
+fn split_factor(ex_date: &str, new_rate: f64, old_rate: f64) -> Result<AdjustmentFactor, Error> {
+    if new_rate <= 0.0 || old_rate <= 0.0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid Split Rate: {new_rate}:{old_rate}"),
+        ));
+    }
+    let ratio = new_rate / old_rate;
+    Ok(AdjustmentFactor {
+        ex_date: parse_date(ex_date)?,
+        price: 1.0 / ratio,
+        volume: ratio,
+    })
+}
+
+/// Adjustment factors of a symbol's corporate actions. Cash dividends are scaled
+/// by the raw close before their ex date, so `bars` must be unadjusted.
+pub fn adjustment_factors(
+    bars: &[StockBar],
+    symbol: &str,
+    actions: &CorporateActions,
+    adjustment: Adjustment,
+) -> Result<Vec<AdjustmentFactor>, Error> {
+    let mut factors = Vec::new();
+
+    if adjustment.splits() {
+        for split in actions.forward_splits.iter().filter(|s| s.symbol == symbol) {
+            factors.push(split_factor(
+                &split.ex_date,
+                split.new_rate,
+                split.old_rate,
+            )?);
+        }
+        for split in actions.reverse_splits.iter().filter(|s| s.symbol == symbol) {
+            factors.push(split_factor(
+                &split.ex_date,
+                split.new_rate,
+                split.old_rate,
+            )?);
+        }
+        for dividend in actions
+            .stock_dividends
+            .iter()
+            .filter(|d| d.symbol == symbol)
+        {
+            factors.push(split_factor(&dividend.ex_date, 1.0 + dividend.rate, 1.0)?);
+        }
+    }
+
+    if adjustment.dividends() {
+        let dates = bars.iter().map(bar_date).collect::<Result<Vec<_>, _>>()?;
+        for dividend in actions.cash_dividends.iter().filter(|d| d.symbol == symbol) {
+            let ex_date = parse_date(&dividend.ex_date)?;
+            let previous_close = dates
+                .iter()
+                .zip(bars)
+                .filter(|(date, _)| **date < ex_date)
+                .max_by_key(|(date, _)| **date)
+                .map(|(_, bar)| bar.c as f64);
+
+            // Without a prior bar there is nothing to adjust
+            if let Some(close) = previous_close.filter(|close| *close > dividend.rate) {
+                factors.push(AdjustmentFactor {
+                    ex_date,
+                    price: 1.0 - dividend.rate / close,
+                    volume: 1.0,
+                });
+            }
+        }
+    }
+
+    factors.sort_by_key(|factor| factor.ex_date);
+    Ok(factors)
+}
+
+/// Apply factors with an ex date on or before `as_of` to raw bars
+pub fn apply_adjustment_factors(
+    bars: &[StockBar],
+    factors: &[AdjustmentFactor],
+    as_of: NaiveDate,
+) -> Result<Vec<StockBar>, Error> {
+    bars.iter()
+        .map(|bar| {
+            let date = bar_date(bar)?;
+            let (price, volume) = factors
+                .iter()
+                .filter(|factor| date < factor.ex_date && factor.ex_date <= as_of)
+                .fold((1.0, 1.0), |(price, volume), factor| {
+                    (price * factor.price, volume * factor.volume)
+                });
+
+            Ok(StockBar {
+                t: bar.t.clone(),
+                o: (bar.o as f64 * price) as f32,
+                h: (bar.h as f64 * price) as f32,
+                l: (bar.l as f64 * price) as f32,
+                c: (bar.c as f64 * price) as f32,
+                v: (bar.v as f64 * volume) as f32,
+                n: bar.n,
+                vw: (bar.vw as f64 * price) as f32,
+            })
+        })
+        .collect()
+}
+
+/// Adjust a symbol's raw bars for its corporate actions as of a date
+pub fn adjust_bars(
+    bars: &[StockBar],
+    symbol: &str,
+    actions: &CorporateActions,
+    adjustment: Adjustment,
+    as_of: NaiveDate,
+) -> Result<Vec<StockBar>, Error> {
+    let factors = adjustment_factors(bars, symbol, actions, adjustment)?;
+    apply_adjustment_factors(bars, &factors, as_of)
+}
+
+/// Adjust raw bars of every symbol for their corporate actions as of a date
+pub fn adjust_historical_bars(
+    bars: &HistoricalBars,
+    actions: &CorporateActions,
+    adjustment: Adjustment,
+    as_of: NaiveDate,
+) -> Result<HistoricalBars, Error> {
+    bars.iter()
+        .map(|(symbol, bars)| {
+            let adjusted = adjust_bars(bars, symbol, actions, adjustment, as_of)?;
+            Ok((symbol.clone(), adjusted))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::corporate_actions::{CashDividend, ForwardSplit};
+
+    fn bar(t: &str, close: f32, volume: f32) -> StockBar {
+        StockBar {
+            t: t.to_string(),
+            o: close,
+            h: close,
+            l: close,
+            c: close,
+            v: volume,
+            n: 1,
+            vw: close,
+        }
+    }
+
+    fn raw_bars() -> Vec<StockBar> {
+        vec![
+            bar("2020-08-27T04:00:00Z", 400.0, 100.0),
+            bar("2020-08-28T04:00:00Z", 500.0, 100.0),
+            bar("2020-08-31T04:00:00Z", 125.0, 400.0),
+            bar("2020-09-01T04:00:00Z", 130.0, 400.0),
+        ]
+    }
+
+    fn actions() -> CorporateActions {
+        CorporateActions {
+            forward_splits: vec![ForwardSplit {
+                id: "split".to_string(),
+                symbol: "AAPL".to_string(),
+                cusip: "037833100".to_string(),
+                new_rate: 4.0,
+                old_rate: 1.0,
+                process_date: "2020-08-31".to_string(),
+                ex_date: "2020-08-31".to_string(),
+                record_date: None,
+                payable_date: None,
+                due_bill_redemption_date: None,
+            }],
+            cash_dividends: vec![CashDividend {
+                id: "dividend".to_string(),
+                symbol: "AAPL".to_string(),
+                cusip: "037833100".to_string(),
+                rate: 1.3,
+                special: false,
+                foreign: false,
+                process_date: "2020-09-01".to_string(),
+                ex_date: "2020-09-01".to_string(),
+                record_date: None,
+                payable_date: None,
+                due_bill_on_date: None,
+                due_bill_off_date: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn approx(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-3, "{value} is not {expected}");
+    }
+
+    #[test]
+    fn test_split_adjustment() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bars = adjust_bars(&raw_bars(), "AAPL", &actions(), Adjustment::Split, as_of).unwrap();
+
+        approx(bars[0].c, 100.0);
+        approx(bars[0].v, 400.0);
+        approx(bars[1].c, 125.0);
+        approx(bars[2].c, 125.0);
+        approx(bars[3].c, 130.0);
+    }
+
+    #[test]
+    fn test_dividend_adjustment() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let bars = adjust_bars(&raw_bars(), "AAPL", &actions(), Adjustment::All, as_of).unwrap();
+
+        // 1.30 on a 125.00 close takes 1.04% off every earlier bar
+        approx(bars[0].c, 100.0 * 0.9896);
+        approx(bars[2].c, 125.0 * 0.9896);
+        approx(bars[2].v, 400.0);
+        approx(bars[3].c, 130.0);
+    }
+
+    #[test]
+    fn test_adjustment_as_of() {
+        let as_of = NaiveDate::from_ymd_opt(2020, 8, 31).unwrap();
+        let bars = adjust_bars(&raw_bars(), "AAPL", &actions(), Adjustment::All, as_of).unwrap();
+
+        approx(bars[1].c, 125.0);
+        approx(bars[2].c, 125.0);
+
+        let bars = adjust_bars(&raw_bars(), "TSLA", &actions(), Adjustment::All, as_of).unwrap();
+        approx(bars[1].c, 500.0);
+    }
+
+    #[test]
+    fn test_adjustment_display() {
+        assert_eq!(Adjustment::Dividend.to_string(), "dividend");
+    }
+}
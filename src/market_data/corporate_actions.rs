@@ -0,0 +1,496 @@
+use crate::request;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CorporateActionType {
+    ForwardSplit,
+    ReverseSplit,
+    UnitSplit,
+    StockDividend,
+    CashDividend,
+    SpinOff,
+    CashMerger,
+    StockMerger,
+    StockAndCashMerger,
+    Redemption,
+    NameChange,
+    WorthlessRemoval,
+    RightsDistribution,
+}
+
+impl CorporateActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorporateActionType::ForwardSplit => "forward_split",
+            CorporateActionType::ReverseSplit => "reverse_split",
+            CorporateActionType::UnitSplit => "unit_split",
+            CorporateActionType::StockDividend => "stock_dividend",
+            CorporateActionType::CashDividend => "cash_dividend",
+            CorporateActionType::SpinOff => "spin_off",
+            CorporateActionType::CashMerger => "cash_merger",
+            CorporateActionType::StockMerger => "stock_merger",
+            CorporateActionType::StockAndCashMerger => "stock_and_cash_merger",
+            CorporateActionType::Redemption => "redemption",
+            CorporateActionType::NameChange => "name_change",
+            CorporateActionType::WorthlessRemoval => "worthless_removal",
+            CorporateActionType::RightsDistribution => "rights_distribution",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForwardSplit {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub new_rate: f64,
+    pub old_rate: f64,
+    pub process_date: String,
+    pub ex_date: String,
+    pub record_date: Option<String>,
+    pub payable_date: Option<String>,
+    pub due_bill_redemption_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReverseSplit {
+    pub id: String,
+    pub symbol: String,
+    pub old_cusip: String,
+    pub new_cusip: String,
+    pub new_rate: f64,
+    pub old_rate: f64,
+    pub process_date: String,
+    pub ex_date: String,
+    pub record_date: Option<String>,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnitSplit {
+    pub id: String,
+    pub old_symbol: String,
+    pub old_cusip: String,
+    pub old_rate: f64,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub new_rate: f64,
+    pub alternate_symbol: String,
+    pub alternate_cusip: String,
+    pub alternate_rate: f64,
+    pub process_date: String,
+    pub effective_date: String,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StockDividend {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub rate: f64,
+    pub process_date: String,
+    pub ex_date: String,
+    pub record_date: Option<String>,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CashDividend {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub rate: f64,
+    pub special: bool,
+    pub foreign: bool,
+    pub process_date: String,
+    pub ex_date: String,
+    pub record_date: Option<String>,
+    pub payable_date: Option<String>,
+    pub due_bill_on_date: Option<String>,
+    pub due_bill_off_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpinOff {
+    pub id: String,
+    pub source_symbol: String,
+    pub source_cusip: String,
+    pub source_rate: f64,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub new_rate: f64,
+    pub process_date: String,
+    pub ex_date: String,
+    pub record_date: Option<String>,
+    pub payable_date: Option<String>,
+    pub due_bill_redemption_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CashMerger {
+    pub id: String,
+    pub acquirer_symbol: Option<String>,
+    pub acquirer_cusip: Option<String>,
+    pub acquiree_symbol: String,
+    pub acquiree_cusip: String,
+    pub rate: f64,
+    pub process_date: String,
+    pub effective_date: String,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StockMerger {
+    pub id: String,
+    pub acquirer_symbol: String,
+    pub acquirer_cusip: String,
+    pub acquirer_rate: f64,
+    pub acquiree_symbol: String,
+    pub acquiree_cusip: String,
+    pub acquiree_rate: f64,
+    pub process_date: String,
+    pub effective_date: String,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StockAndCashMerger {
+    pub id: String,
+    pub acquirer_symbol: String,
+    pub acquirer_cusip: String,
+    pub acquirer_rate: f64,
+    pub acquiree_symbol: String,
+    pub acquiree_cusip: String,
+    pub acquiree_rate: f64,
+    pub cash_rate: f64,
+    pub process_date: String,
+    pub effective_date: String,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Redemption {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub rate: f64,
+    pub process_date: String,
+    pub payable_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NameChange {
+    pub id: String,
+    pub old_symbol: String,
+    pub old_cusip: String,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub process_date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorthlessRemoval {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub process_date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RightsDistribution {
+    pub id: String,
+    pub source_symbol: String,
+    pub source_cusip: String,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub rate: f64,
+    pub process_date: String,
+    pub ex_date: String,
+    pub payable_date: Option<String>,
+    pub record_date: Option<String>,
+    pub expiration_date: Option<String>,
+}
+
+/// Corporate actions grouped by kind, kinds missing from a response are left empty
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CorporateActions {
+    pub forward_splits: Vec<ForwardSplit>,
+    pub reverse_splits: Vec<ReverseSplit>,
+    pub unit_splits: Vec<UnitSplit>,
+    pub stock_dividends: Vec<StockDividend>,
+    pub cash_dividends: Vec<CashDividend>,
+    pub spin_offs: Vec<SpinOff>,
+    pub cash_mergers: Vec<CashMerger>,
+    pub stock_mergers: Vec<StockMerger>,
+    pub stock_and_cash_mergers: Vec<StockAndCashMerger>,
+    pub redemptions: Vec<Redemption>,
+    pub name_changes: Vec<NameChange>,
+    pub worthless_removals: Vec<WorthlessRemoval>,
+    pub rights_distributions: Vec<RightsDistribution>,
+}
+
+impl CorporateActions {
+    /// Total number of actions across every kind
+    pub fn len(&self) -> usize {
+        self.forward_splits.len()
+            + self.reverse_splits.len()
+            + self.unit_splits.len()
+            + self.stock_dividends.len()
+            + self.cash_dividends.len()
+            + self.spin_offs.len()
+            + self.cash_mergers.len()
+            + self.stock_mergers.len()
+            + self.stock_and_cash_mergers.len()
+            + self.redemptions.len()
+            + self.name_changes.len()
+            + self.worthless_removals.len()
+            + self.rights_distributions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append every action of another page onto this one
+    pub fn extend(&mut self, other: CorporateActions) {
+        self.forward_splits.extend(other.forward_splits);
+        self.reverse_splits.extend(other.reverse_splits);
+        self.unit_splits.extend(other.unit_splits);
+        self.stock_dividends.extend(other.stock_dividends);
+        self.cash_dividends.extend(other.cash_dividends);
+        self.spin_offs.extend(other.spin_offs);
+        self.cash_mergers.extend(other.cash_mergers);
+        self.stock_mergers.extend(other.stock_mergers);
+        self.stock_and_cash_mergers
+            .extend(other.stock_and_cash_mergers);
+        self.redemptions.extend(other.redemptions);
+        self.name_changes.extend(other.name_changes);
+        self.worthless_removals.extend(other.worthless_removals);
+        self.rights_distributions.extend(other.rights_distributions);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CorporateActionsResponse {
+    pub corporate_actions: CorporateActions,
+    pub next_page_token: Option<String>,
+}
+
+pub struct CorporateActionsQuery<'a> {
+    url: &'a str,
+    symbols: Vec<&'a str>,
+    cusips: Vec<&'a str>,
+    types: Vec<CorporateActionType>,
+    start: Option<&'a str>,
+    end: Option<&'a str>,
+    limit: Option<i32>,
+    sort_desc: bool,
+    sort_asc: bool,
+}
+
+impl<'a> CorporateActionsQuery<'a> {
+    pub fn new(symbols: Vec<&'a str>) -> Self {
+        Self {
+            url: "https://data.alpaca.markets/v1/corporate-actions",
+            symbols,
+            cusips: Vec::new(),
+            types: Vec::new(),
+            start: None,
+            end: None,
+            limit: None,
+            sort_desc: false,
+            sort_asc: false,
+        }
+    }
+
+    pub fn cusips(mut self, cusips: Vec<&'a str>) -> Self {
+        self.cusips = cusips;
+        self
+    }
+
+    /// Only return these kinds of action, all kinds are returned by default
+    pub fn types(mut self, types: Vec<CorporateActionType>) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// Inclusive start date, in `YYYY-MM-DD` format
+    pub fn start(mut self, start: &'a str) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Inclusive end date, in `YYYY-MM-DD` format
+    pub fn end(mut self, end: &'a str) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn sort_desc(mut self) -> Self {
+        self.sort_desc = true;
+        self.sort_asc = false;
+        self
+    }
+
+    pub fn sort_asc(mut self) -> Self {
+        self.sort_asc = true;
+        self.sort_desc = false;
+        self
+    }
+
+    fn build(&self) -> String {
+        let mut params = Vec::new();
+        if !self.symbols.is_empty() {
+            params.push(format!("symbols={}", self.symbols.join(",")));
+        }
+        if !self.cusips.is_empty() {
+            params.push(format!("cusips={}", self.cusips.join(",")));
+        }
+        if !self.types.is_empty() {
+            let types: Vec<&str> = self.types.iter().map(|t| t.as_str()).collect();
+            params.push(format!("types={}", types.join(",")));
+        }
+        if let Some(start) = self.start {
+            params.push(format!("start={}", start));
+        }
+        if let Some(end) = self.end {
+            params.push(format!("end={}", end));
+        }
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+        if self.sort_asc {
+            params.push("sort=asc".to_string());
+        } else if self.sort_desc {
+            params.push("sort=desc".to_string());
+        }
+
+        format!("{}?{}", self.url, params.join("&"))
+    }
+
+    pub fn send(&self) -> Result<CorporateActions, ureq::Error> {
+        let route = self.build();
+        let mut actions = CorporateActions::default();
+        let mut page_token = None;
+
+        loop {
+            // If a token exists, append to address
+            let temp_address = match page_token {
+                Some(token) => format!("{}&page_token={}", &route, &token),
+                _ => route.clone(),
+            };
+            let response = request("GET", &temp_address).call()?;
+            let response: CorporateActionsResponse = response.into_json()?;
+            actions.extend(response.corporate_actions);
+
+            if let Some(limit) = self.limit {
+                if actions.len() >= limit as usize {
+                    break;
+                }
+            }
+
+            // If a token is in response, assign to page_token for next loop
+            match response.next_page_token {
+                Some(next_page_token) => page_token = Some(next_page_token),
+                _ => break,
+            }
+        }
+
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_corporate_actions_query() {
+        let route = CorporateActionsQuery::new(vec!["AAPL", "TSLA"])
+            .types(vec![
+                CorporateActionType::ForwardSplit,
+                CorporateActionType::CashDividend,
+            ])
+            .start("2020-01-01")
+            .end("2020-12-31")
+            .limit(50)
+            .sort_asc()
+            .build();
+
+        assert_eq!(
+            route,
+            "https://data.alpaca.markets/v1/corporate-actions?symbols=AAPL,TSLA\
+            &types=forward_split,cash_dividend&start=2020-01-01&end=2020-12-31&limit=50&sort=asc"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_corporate_actions() {
+        let response: CorporateActionsResponse = serde_json::from_str(
+            r#"{
+                "corporate_actions": {
+                    "forward_splits": [{
+                        "id": "e4ba0f4e-f4fa-4c1b-bc83-2b7dbb0e9a6a",
+                        "symbol": "AAPL",
+                        "cusip": "037833100",
+                        "new_rate": 4,
+                        "old_rate": 1,
+                        "process_date": "2020-08-31",
+                        "ex_date": "2020-08-31",
+                        "record_date": "2020-08-24",
+                        "payable_date": "2020-08-28"
+                    }],
+                    "cash_dividends": [{
+                        "id": "0b5d8cbb-2f2b-4ba2-9c4a-9c1e3f7f8b01",
+                        "symbol": "AAPL",
+                        "cusip": "037833100",
+                        "rate": 0.205,
+                        "special": false,
+                        "foreign": false,
+                        "process_date": "2020-08-14",
+                        "ex_date": "2020-08-07",
+                        "record_date": "2020-08-10",
+                        "payable_date": "2020-08-13"
+                    }],
+                    "name_changes": [{
+                        "id": "6e3c6d1a-5c3f-4f4d-8d0b-1b5c6c3b1a2d",
+                        "old_symbol": "FB",
+                        "old_cusip": "30303M102",
+                        "new_symbol": "META",
+                        "new_cusip": "30303M102",
+                        "process_date": "2022-06-09"
+                    }]
+                },
+                "next_page_token": null
+            }"#,
+        )
+        .unwrap();
+
+        let actions = response.corporate_actions;
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions.forward_splits[0].new_rate, 4.0);
+        assert_eq!(actions.cash_dividends[0].rate, 0.205);
+        assert_eq!(actions.name_changes[0].new_symbol, "META");
+        assert!(actions.reverse_splits.is_empty());
+    }
+
+    #[test]
+    fn test_corporate_actions_query() {
+        let actions = CorporateActionsQuery::new(vec!["AAPL"])
+            .start("2020-01-01")
+            .end("2020-12-31")
+            .send()
+            .unwrap();
+
+        dbg!(&actions);
+        assert!(!actions.forward_splits.is_empty());
+    }
+}
@@ -65,7 +65,7 @@ impl<'a> HistoricalCryptoBarsQuery<'a> {
         let mut query = format!(
             "symbols={}&timeframe={}",
             self.symbols.join(","),
-            self.timeframe.to_string()
+            self.timeframe
         );
 
         if let Some(start) = self.start {
@@ -1,15 +1,15 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Orderbook {
     pub t: String,
     pub b: Vec<PriceSize>,
     pub a: Vec<PriceSize>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct PriceSize {
     pub p: f64,
     pub s: f64,
@@ -17,7 +17,7 @@ pub struct PriceSize {
 
 pub type Orderbooks = HashMap<String, Orderbook>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OrderbookResponse {
     pub orderbooks: Orderbooks,
 }
@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CryptoQuote {
     pub t: String,
     pub bp: f64,
@@ -13,13 +13,13 @@ pub struct CryptoQuote {
 pub type HistoricalCryptoQuotes = HashMap<String, Vec<CryptoQuote>>;
 pub type LatestCryptoQuotes = HashMap<String, CryptoQuote>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalCryptoQuoteResponse {
     quotes: HistoricalCryptoQuotes,
     next_page_token: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestCryptoQuoteResponse {
     pub quotes: LatestCryptoQuotes,
 }
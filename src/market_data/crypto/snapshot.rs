@@ -1,9 +1,9 @@
 use super::{quotes::CryptoQuote, trades::CryptoTrade};
 use crate::{market_data::stocks::StockBar, request};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CryptoSnapshot {
     pub daily_bar: Option<StockBar>,
@@ -15,7 +15,7 @@ pub struct CryptoSnapshot {
 
 pub type CryptoSnapshots = HashMap<String, CryptoSnapshot>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CryptoSnapshotsResponse {
     pub snapshots: CryptoSnapshots,
 }
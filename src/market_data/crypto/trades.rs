@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CryptoTrade {
     pub t: String,   // Timestamp
     pub p: f64,      // Price
@@ -14,13 +14,13 @@ pub struct CryptoTrade {
 pub type HistoricalCryptoTrades = HashMap<String, Vec<CryptoTrade>>;
 pub type LatestCryptoTrades = HashMap<String, CryptoTrade>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalCryptoTradesResponse {
     trades: HistoricalCryptoTrades,
     next_page_token: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestCryptoTradesResponse {
     trades: LatestCryptoTrades,
 }
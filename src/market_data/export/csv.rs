@@ -0,0 +1,124 @@
+use super::{sorted_symbols, FieldKind, FieldValue, Record, SYMBOL_COLUMN};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+};
+
+/// Write a symbol keyed data map as CSV, one row per record with a leading symbol column
+pub fn write_csv<T: Record, W: Write>(data: &HashMap<String, Vec<T>>, writer: W) -> io::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(writer);
+
+    let mut header = vec![SYMBOL_COLUMN];
+    header.extend(T::fields().iter().map(|(name, _)| *name));
+    writer.write_record(&header)?;
+
+    for symbol in sorted_symbols(data) {
+        for record in &data[symbol] {
+            let mut row = vec![symbol.clone()];
+            for value in record.to_values() {
+                row.push(to_cell(value)?);
+            }
+            writer.write_record(&row)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read CSV written by [`write_csv`] back into a symbol keyed data map
+pub fn read_csv<T: Record, R: Read>(reader: R) -> io::Result<HashMap<String, Vec<T>>> {
+    let mut reader = ::csv::Reader::from_reader(reader);
+
+    let header = reader.headers()?.clone();
+    let expected = std::iter::once(SYMBOL_COLUMN).chain(T::fields().iter().map(|(name, _)| *name));
+    if !header.iter().eq(expected) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "CSV Header Does Not Match Record",
+        ));
+    }
+
+    let mut data: HashMap<String, Vec<T>> = HashMap::new();
+    for row in reader.records() {
+        let row = row?;
+        let mut cells = row.iter();
+        let symbol = cells.next().unwrap_or_default().to_string();
+
+        let mut values = Vec::with_capacity(T::fields().len());
+        for ((_, kind), cell) in T::fields().iter().zip(cells) {
+            values.push(from_cell(*kind, cell)?);
+        }
+
+        data.entry(symbol)
+            .or_default()
+            .push(T::from_values(values)?);
+    }
+
+    Ok(data)
+}
+
+fn to_cell(value: FieldValue) -> io::Result<String> {
+    Ok(match value {
+        FieldValue::Text(value) => value,
+        FieldValue::Float(value) => value.to_string(),
+        FieldValue::Integer(value) => value.to_string(),
+        // Condition codes may hold commas or spaces, so lists are stored as a JSON array
+        FieldValue::TextList(value) => serde_json::to_string(&value)?,
+    })
+}
+
+fn from_cell(kind: FieldKind, cell: &str) -> io::Result<FieldValue> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid Cell: {cell}"));
+    Ok(match kind {
+        FieldKind::Text | FieldKind::Timestamp => FieldValue::Text(cell.to_string()),
+        FieldKind::Float => FieldValue::Float(cell.parse().map_err(|_| invalid())?),
+        FieldKind::Integer => FieldValue::Integer(cell.parse().map_err(|_| invalid())?),
+        FieldKind::TextList if cell.is_empty() => FieldValue::TextList(Vec::new()),
+        FieldKind::TextList => FieldValue::TextList(serde_json::from_str(cell)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::{
+        export::tests::{sample_bars, sample_trades},
+        stocks::{HistoricalBars, HistoricalTrades, StockBar},
+    };
+
+    #[test]
+    fn test_bars_csv_round_trip() {
+        let bars = sample_bars();
+        let mut buffer = Vec::new();
+        write_csv(&bars, &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.starts_with("symbol,t,o,h,l,c,v,n,vw\nAAPL,2022-02-01T05:00:00Z,174,174.78"));
+
+        let restored: HistoricalBars = read_csv(buffer.as_slice()).unwrap();
+        assert_eq!(restored["AAPL"].len(), 2);
+        assert_eq!(restored["TSLA"][0].vw, bars["TSLA"][0].vw);
+    }
+
+    #[test]
+    fn test_trades_csv_round_trip() {
+        let trades = sample_trades();
+        let mut buffer = Vec::new();
+        write_csv(&trades, &mut buffer).unwrap();
+
+        let restored: HistoricalTrades = read_csv(buffer.as_slice()).unwrap();
+        assert_eq!(restored["AAPL"][0].c, vec!["@", "I"]);
+        assert_eq!(restored["AAPL"][0].p, 228.87);
+    }
+
+    #[test]
+    fn test_csv_header_mismatch() {
+        let trades = sample_trades();
+        let mut buffer = Vec::new();
+        write_csv(&trades, &mut buffer).unwrap();
+
+        let restored = read_csv::<StockBar, _>(buffer.as_slice());
+        assert!(restored.is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use super::{sorted_symbols, SYMBOL_COLUMN};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+/// Write a symbol keyed data map as JSON Lines, one object per record with a symbol field
+pub fn write_jsonl<T: Serialize, W: Write>(
+    data: &HashMap<String, Vec<T>>,
+    mut writer: W,
+) -> io::Result<()> {
+    for symbol in sorted_symbols(data) {
+        for record in &data[symbol] {
+            let mut value = serde_json::to_value(record)?;
+            match value.as_object_mut() {
+                Some(object) => {
+                    object.insert(SYMBOL_COLUMN.to_string(), Value::from(symbol.as_str()))
+                }
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Record Is Not A JSON Object",
+                    ))
+                }
+            };
+            serde_json::to_writer(&mut writer, &value)?;
+            writer.write_all(b"\n")?;
+        }
+    }
+
+    writer.flush()
+}
+
+/// Read JSON Lines written by [`write_jsonl`] back into a symbol keyed data map
+pub fn read_jsonl<T: DeserializeOwned, R: BufRead>(
+    reader: R,
+) -> io::Result<HashMap<String, Vec<T>>> {
+    let mut data: HashMap<String, Vec<T>> = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut value: Value = serde_json::from_str(&line)?;
+        let symbol = value
+            .as_object_mut()
+            .and_then(|object| object.remove(SYMBOL_COLUMN))
+            .and_then(|symbol| symbol.as_str().map(str::to_string))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Symbol Field"))?;
+
+        data.entry(symbol)
+            .or_default()
+            .push(serde_json::from_value(value)?);
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::{
+        export::tests::{sample_bars, sample_trades},
+        stocks::{HistoricalBars, HistoricalTrades},
+    };
+
+    #[test]
+    fn test_bars_jsonl_round_trip() {
+        let bars = sample_bars();
+        let mut buffer = Vec::new();
+        write_jsonl(&bars, &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.lines().next().unwrap().contains("\"symbol\":\"AAPL\""));
+
+        let restored: HistoricalBars = read_jsonl(buffer.as_slice()).unwrap();
+        assert_eq!(restored["AAPL"][1].t, "2022-02-02T05:00:00Z");
+        assert_eq!(restored["TSLA"][0].n, 17534);
+    }
+
+    #[test]
+    fn test_trades_jsonl_round_trip() {
+        let trades = sample_trades();
+        let mut buffer = Vec::new();
+        write_jsonl(&trades, &mut buffer).unwrap();
+
+        let restored: HistoricalTrades = read_jsonl(buffer.as_slice()).unwrap();
+        assert_eq!(restored["AAPL"][0].c, vec!["@", "I"]);
+    }
+
+    #[test]
+    fn test_jsonl_missing_symbol() {
+        let line = r#"{"t":"2022-02-01T05:00:00Z","o":1,"h":1,"l":1,"c":1,"v":1,"n":1,"vw":1}"#;
+        let restored = read_jsonl::<crate::market_data::stocks::StockBar, _>(line.as_bytes());
+        assert!(restored.is_err());
+    }
+}
@@ -0,0 +1,468 @@
+use crate::market_data::{
+    crypto::{CryptoQuote, CryptoTrade},
+    options::{OptionQuote, OptionTrade},
+    stocks::{StockBar, StockQuote, StockTrade},
+};
+use std::{collections::HashMap, io};
+
+mod csv;
+pub use self::csv::*;
+
+mod jsonl;
+pub use jsonl::*;
+
+#[cfg(feature = "parquet")]
+mod parquet;
+#[cfg(feature = "parquet")]
+pub use self::parquet::*;
+
+/// Name of the column holding the symbol in every export format
+pub const SYMBOL_COLUMN: &str = "symbol";
+
+/// Column types used when exporting records
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FieldKind {
+    Text,
+    /// RFC-3339 text in CSV and JSON, a UTC timestamp column in Arrow and Parquet
+    Timestamp,
+    Float,
+    Integer,
+    TextList,
+}
+
+/// A single exported value
+#[derive(Debug, PartialEq, Clone)]
+pub enum FieldValue {
+    Text(String),
+    Float(f64),
+    Integer(i64),
+    TextList(Vec<String>),
+}
+
+/// A market data object that can be flattened into a row of columns
+pub trait Record: Sized {
+    /// Column names and kinds in export order, without the symbol column
+    fn fields() -> &'static [(&'static str, FieldKind)];
+
+    fn to_values(&self) -> Vec<FieldValue>;
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self>;
+}
+
+/// Symbols of a data map in a stable order so exports are deterministic
+fn sorted_symbols<T>(data: &HashMap<String, Vec<T>>) -> Vec<&String> {
+    let mut symbols: Vec<&String> = data.keys().collect();
+    symbols.sort();
+    symbols
+}
+
+/// Widen an f32 through its shortest representation, so 174.54 stays 174.54
+fn widen(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+/// Pulls typed values out of a row in column order
+struct Values(std::vec::IntoIter<FieldValue>);
+
+impl Values {
+    fn new(values: Vec<FieldValue>) -> Self {
+        Self(values.into_iter())
+    }
+
+    fn next(&mut self) -> io::Result<FieldValue> {
+        self.0
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Column"))
+    }
+
+    fn text(&mut self) -> io::Result<String> {
+        match self.next()? {
+            FieldValue::Text(value) => Ok(value),
+            other => Err(mismatch("text", &other)),
+        }
+    }
+
+    fn float(&mut self) -> io::Result<f64> {
+        match self.next()? {
+            FieldValue::Float(value) => Ok(value),
+            FieldValue::Integer(value) => Ok(value as f64),
+            other => Err(mismatch("float", &other)),
+        }
+    }
+
+    fn integer(&mut self) -> io::Result<i64> {
+        match self.next()? {
+            FieldValue::Integer(value) => Ok(value),
+            other => Err(mismatch("integer", &other)),
+        }
+    }
+
+    fn text_list(&mut self) -> io::Result<Vec<String>> {
+        match self.next()? {
+            FieldValue::TextList(value) => Ok(value),
+            other => Err(mismatch("text list", &other)),
+        }
+    }
+}
+
+fn mismatch(expected: &str, found: &FieldValue) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Expected {expected} column, found {found:?}"),
+    )
+}
+
+fn narrow<T: TryFrom<i64>>(value: i64) -> io::Result<T> {
+    T::try_from(value)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Integer Out Of Range"))
+}
+
+impl Record for StockBar {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("o", FieldKind::Float),
+            ("h", FieldKind::Float),
+            ("l", FieldKind::Float),
+            ("c", FieldKind::Float),
+            ("v", FieldKind::Float),
+            ("n", FieldKind::Integer),
+            ("vw", FieldKind::Float),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Float(widen(self.o)),
+            FieldValue::Float(widen(self.h)),
+            FieldValue::Float(widen(self.l)),
+            FieldValue::Float(widen(self.c)),
+            FieldValue::Float(widen(self.v)),
+            FieldValue::Integer(self.n as i64),
+            FieldValue::Float(widen(self.vw)),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            o: values.float()? as f32,
+            h: values.float()? as f32,
+            l: values.float()? as f32,
+            c: values.float()? as f32,
+            v: values.float()? as f32,
+            n: narrow(values.integer()?)?,
+            vw: values.float()? as f32,
+        })
+    }
+}
+
+impl Record for StockTrade {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("x", FieldKind::Text),
+            ("p", FieldKind::Float),
+            ("s", FieldKind::Integer),
+            ("c", FieldKind::TextList),
+            ("i", FieldKind::Integer),
+            ("z", FieldKind::Text),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Text(self.x.clone()),
+            FieldValue::Float(self.p),
+            FieldValue::Integer(self.s as i64),
+            FieldValue::TextList(self.c.clone()),
+            FieldValue::Integer(self.i as i64),
+            FieldValue::Text(self.z.clone()),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            x: values.text()?,
+            p: values.float()?,
+            s: narrow(values.integer()?)?,
+            c: values.text_list()?,
+            i: narrow(values.integer()?)?,
+            z: values.text()?,
+        })
+    }
+}
+
+impl Record for StockQuote {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("ax", FieldKind::Text),
+            ("ap", FieldKind::Float),
+            ("as", FieldKind::Integer),
+            ("bx", FieldKind::Text),
+            ("bp", FieldKind::Float),
+            ("bs", FieldKind::Integer),
+            ("c", FieldKind::TextList),
+            ("z", FieldKind::Text),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Text(self.ax.clone()),
+            FieldValue::Float(widen(self.ap)),
+            FieldValue::Integer(self.r#as as i64),
+            FieldValue::Text(self.bx.clone()),
+            FieldValue::Float(widen(self.bp)),
+            FieldValue::Integer(self.bs as i64),
+            FieldValue::TextList(self.c.clone()),
+            FieldValue::Text(self.z.clone()),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            ax: values.text()?,
+            ap: values.float()? as f32,
+            r#as: narrow(values.integer()?)?,
+            bx: values.text()?,
+            bp: values.float()? as f32,
+            bs: narrow(values.integer()?)?,
+            c: values.text_list()?,
+            z: values.text()?,
+        })
+    }
+}
+
+impl Record for CryptoTrade {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("p", FieldKind::Float),
+            ("s", FieldKind::Float),
+            ("tks", FieldKind::Text),
+            ("i", FieldKind::Integer),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Float(self.p),
+            FieldValue::Float(self.s),
+            FieldValue::Text(self.tks.clone()),
+            FieldValue::Integer(self.i),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            p: values.float()?,
+            s: values.float()?,
+            tks: values.text()?,
+            i: values.integer()?,
+        })
+    }
+}
+
+impl Record for CryptoQuote {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("bp", FieldKind::Float),
+            ("bs", FieldKind::Float),
+            ("ap", FieldKind::Float),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Float(self.bp),
+            FieldValue::Float(self.bs),
+            FieldValue::Float(self.ap),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            bp: values.float()?,
+            bs: values.float()?,
+            ap: values.float()?,
+        })
+    }
+}
+
+impl Record for OptionTrade {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("x", FieldKind::Text),
+            ("p", FieldKind::Float),
+            ("s", FieldKind::Integer),
+            ("c", FieldKind::Text),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Text(self.x.clone()),
+            FieldValue::Float(self.p),
+            FieldValue::Integer(self.s as i64),
+            FieldValue::Text(self.c.clone()),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            x: values.text()?,
+            p: values.float()?,
+            s: narrow(values.integer()?)?,
+            c: values.text()?,
+        })
+    }
+}
+
+impl Record for OptionQuote {
+    fn fields() -> &'static [(&'static str, FieldKind)] {
+        &[
+            ("t", FieldKind::Timestamp),
+            ("ax", FieldKind::Text),
+            ("ap", FieldKind::Float),
+            ("as", FieldKind::Integer),
+            ("bx", FieldKind::Text),
+            ("bp", FieldKind::Float),
+            ("bs", FieldKind::Integer),
+            ("c", FieldKind::Text),
+        ]
+    }
+
+    fn to_values(&self) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Text(self.t.clone()),
+            FieldValue::Text(self.ax.clone()),
+            FieldValue::Float(widen(self.ap)),
+            FieldValue::Integer(self.r#as as i64),
+            FieldValue::Text(self.bx.clone()),
+            FieldValue::Float(widen(self.bp)),
+            FieldValue::Integer(self.bs as i64),
+            FieldValue::Text(self.c.clone()),
+        ]
+    }
+
+    fn from_values(values: Vec<FieldValue>) -> io::Result<Self> {
+        let mut values = Values::new(values);
+        Ok(Self {
+            t: values.text()?,
+            ax: values.text()?,
+            ap: values.float()? as f32,
+            r#as: narrow(values.integer()?)?,
+            bx: values.text()?,
+            bp: values.float()? as f32,
+            bs: narrow(values.integer()?)?,
+            c: values.text()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::stocks::{HistoricalBars, HistoricalTrades};
+
+    pub(super) fn sample_bars() -> HistoricalBars {
+        let mut bars = HashMap::new();
+        bars.insert(
+            "AAPL".to_string(),
+            vec![
+                StockBar {
+                    t: "2022-02-01T05:00:00Z".to_string(),
+                    o: 174.0,
+                    h: 174.78,
+                    l: 172.36,
+                    c: 174.54,
+                    v: 1094581.0,
+                    n: 10621,
+                    vw: 173.47993,
+                },
+                StockBar {
+                    t: "2022-02-02T05:00:00Z".to_string(),
+                    o: 174.82,
+                    h: 175.88,
+                    l: 173.33,
+                    c: 175.58,
+                    v: 1304266.0,
+                    n: 11695,
+                    vw: 174.82686,
+                },
+            ],
+        );
+        bars.insert(
+            "TSLA".to_string(),
+            vec![StockBar {
+                t: "2022-02-01T05:00:00Z".to_string(),
+                o: 934.4,
+                h: 943.35,
+                l: 905.02,
+                c: 930.48,
+                v: 651095.0,
+                n: 17534,
+                vw: 926.5979,
+            }],
+        );
+        bars
+    }
+
+    pub(super) fn sample_trades() -> HistoricalTrades {
+        let mut trades = HashMap::new();
+        trades.insert(
+            "AAPL".to_string(),
+            vec![StockTrade {
+                t: "2024-09-20T14:28:58.123Z".to_string(),
+                x: "V".to_string(),
+                p: 228.87,
+                s: 100,
+                c: vec!["@".to_string(), "I".to_string()],
+                i: 3285,
+                z: "C".to_string(),
+            }],
+        );
+        trades
+    }
+
+    #[test]
+    fn test_stock_bar_values_round_trip() {
+        let bars = sample_bars();
+        let bar = &bars["AAPL"][0];
+        let values = bar.to_values();
+
+        assert_eq!(values.len(), StockBar::fields().len());
+        assert_eq!(values[4], FieldValue::Float(174.54));
+
+        let restored = StockBar::from_values(values).unwrap();
+        assert_eq!(restored.c, bar.c);
+        assert_eq!(restored.n, bar.n);
+    }
+
+    #[test]
+    fn test_mismatched_values_are_rejected() {
+        let values = vec![FieldValue::Float(1.0)];
+        assert!(StockBar::from_values(values).is_err());
+    }
+}
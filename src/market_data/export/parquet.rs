@@ -0,0 +1,74 @@
+use super::Record;
+use crate::arrow::{records_from_batch, ToRecordBatch};
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+    file::reader::ChunkReader,
+};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// Write a symbol keyed data map as a Parquet file with a leading symbol column
+pub fn write_parquet<T: Record, W: Write + Send>(
+    data: &HashMap<String, Vec<T>>,
+    writer: W,
+) -> io::Result<()> {
+    let batch = data.to_record_batch().map_err(io::Error::other)?;
+    let mut writer =
+        ArrowWriter::try_new(writer, batch.schema(), None).map_err(io::Error::other)?;
+    writer.write(&batch).map_err(io::Error::other)?;
+    writer.close().map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// Read Parquet written by [`write_parquet`] back into a symbol keyed data map
+pub fn read_parquet<T: Record, R: ChunkReader + 'static>(
+    reader: R,
+) -> io::Result<HashMap<String, Vec<T>>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(reader)
+        .and_then(|builder| builder.build())
+        .map_err(io::Error::other)?;
+
+    let mut data: HashMap<String, Vec<T>> = HashMap::new();
+    for batch in reader {
+        let batch = batch.map_err(io::Error::other)?;
+        for (symbol, records) in records_from_batch(&batch).map_err(io::Error::other)? {
+            data.entry(symbol).or_default().extend(records);
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::{
+        export::tests::{sample_bars, sample_trades},
+        stocks::{HistoricalBars, HistoricalTrades},
+    };
+
+    #[test]
+    fn test_bars_parquet_round_trip() {
+        let bars = sample_bars();
+        let mut buffer = Vec::new();
+        write_parquet(&bars, &mut buffer).unwrap();
+
+        let restored: HistoricalBars = read_parquet(bytes::Bytes::from(buffer)).unwrap();
+        assert_eq!(restored["AAPL"].len(), 2);
+        assert_eq!(restored["AAPL"][0].c, bars["AAPL"][0].c);
+        assert_eq!(restored["AAPL"][0].t, bars["AAPL"][0].t);
+    }
+
+    #[test]
+    fn test_trades_parquet_round_trip() {
+        let trades = sample_trades();
+        let mut buffer = Vec::new();
+        write_parquet(&trades, &mut buffer).unwrap();
+
+        let restored: HistoricalTrades = read_parquet(bytes::Bytes::from(buffer)).unwrap();
+        assert_eq!(restored["AAPL"][0].c, vec!["@", "I"]);
+        assert_eq!(restored["AAPL"][0].t, "2024-09-20T14:28:58.123Z");
+    }
+}
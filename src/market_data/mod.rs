@@ -1,5 +1,9 @@
+pub mod adjustment;
+pub mod corporate_actions;
 pub mod crypto;
+pub mod export;
 pub mod news;
 pub mod options;
+pub mod resample;
 pub mod screener;
 pub mod stocks;
@@ -1,7 +1,7 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct NewsArticle {
     pub author: String,
     pub content: String,
@@ -16,7 +16,7 @@ pub struct NewsArticle {
     pub url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct NewsImage {
     pub url: String,
     pub size: String,
@@ -24,7 +24,7 @@ pub struct NewsImage {
 
 pub type News = Vec<NewsArticle>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct NewsResponse {
     pub news: News,
     pub next_page_token: Option<String>,
@@ -16,10 +16,10 @@ pub struct HistoricalOptionBarsQuery<'a> {
 }
 
 impl<'a> HistoricalOptionBarsQuery<'a> {
-    pub fn new(symbols: Vec<&'a str>, timeframe: TimeFrame) -> Self {
+    pub fn new<S: AsRef<str> + ?Sized>(symbols: Vec<&'a S>, timeframe: TimeFrame) -> Self {
         Self {
             url: "https://data.alpaca.markets/v1beta1/options/bars",
-            symbols,
+            symbols: symbols.into_iter().map(AsRef::as_ref).collect(),
             timeframe,
             start: None,
             end: None,
@@ -60,7 +60,7 @@ impl<'a> HistoricalOptionBarsQuery<'a> {
         let mut query = format!(
             "symbols={}&timeframe={}",
             self.symbols.join(","),
-            self.timeframe.to_string()
+            self.timeframe
         );
 
         if let Some(start) = self.start {
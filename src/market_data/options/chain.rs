@@ -1,6 +1,10 @@
-use super::snapshot::{OptionSnapshotResponse, OptionSnapshots};
-use crate::request;
-use std::collections::HashMap;
+use super::snapshot::{OptionSnapshot, OptionSnapshotResponse, OptionSnapshots};
+use crate::{request, OccSymbol, OptionType};
+use chrono::NaiveDate;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Error,
+};
 
 pub struct OptionChainQuery<'a> {
     url: &'a str,
@@ -191,6 +195,195 @@ impl<'a> OptionChainQuery<'a> {
 
         Ok(snapshots)
     }
+
+    /// Send the query and arrange the snapshots into an [`OptionChain`]
+    pub fn send_chain(&self) -> Result<OptionChain, ureq::Error> {
+        Ok(OptionChain::from_snapshots(self.send()?)?)
+    }
+}
+
+/// Where a strike sits relative to the underlying price
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Moneyness {
+    InTheMoney,
+    AtTheMoney,
+    OutOfTheMoney,
+}
+
+/// A snapshot together with its parsed symbol
+#[derive(Debug)]
+pub struct ChainContract {
+    pub symbol: OccSymbol,
+    pub snapshot: OptionSnapshot,
+}
+
+impl ChainContract {
+    pub fn delta(&self) -> Option<f64> {
+        self.snapshot.greeks.as_ref().map(|greeks| greeks.delta)
+    }
+
+    pub fn moneyness(&self, underlying_price: f64) -> Moneyness {
+        let strike = self.symbol.strike();
+        if strike == underlying_price {
+            return Moneyness::AtTheMoney;
+        }
+        let in_the_money = match self.symbol.option_type() {
+            OptionType::Call => strike < underlying_price,
+            OptionType::Put => strike > underlying_price,
+        };
+        if in_the_money {
+            Moneyness::InTheMoney
+        } else {
+            Moneyness::OutOfTheMoney
+        }
+    }
+}
+
+/// The call and put sharing a root, expiration and strike
+#[derive(Debug)]
+pub struct StrikePair {
+    pub root: String,
+    pub strike: f64,
+    pub call: Option<ChainContract>,
+    pub put: Option<ChainContract>,
+}
+
+impl StrikePair {
+    pub fn contracts(&self) -> impl Iterator<Item = &ChainContract> {
+        self.call.iter().chain(self.put.iter())
+    }
+}
+
+/// An option chain grouped into strike ladders per expiration
+///
+/// Ladders are sorted by strike then root, so chains holding several roots such as
+/// SPX and SPXW keep them apart.
+#[derive(Debug, Default)]
+pub struct OptionChain {
+    expirations: BTreeMap<NaiveDate, Vec<StrikePair>>,
+}
+
+impl OptionChain {
+    /// Arrange symbol keyed snapshots, erroring on any key that is not an OCC symbol
+    pub fn from_snapshots(snapshots: OptionSnapshots) -> Result<Self, Error> {
+        let mut chain = Self::default();
+        for (symbol, snapshot) in snapshots {
+            let symbol: OccSymbol = symbol.parse()?;
+            chain.insert(ChainContract { symbol, snapshot });
+        }
+        Ok(chain)
+    }
+
+    fn insert(&mut self, contract: ChainContract) {
+        let ladder = self
+            .expirations
+            .entry(contract.symbol.expiration())
+            .or_default();
+        let (root, strike) = (contract.symbol.root(), contract.symbol.strike());
+
+        let position = ladder.binary_search_by(|pair| {
+            pair.strike
+                .total_cmp(&strike)
+                .then_with(|| pair.root.as_str().cmp(root))
+        });
+        let index = match position {
+            Ok(index) => index,
+            Err(index) => {
+                let pair = StrikePair {
+                    root: root.to_string(),
+                    strike,
+                    call: None,
+                    put: None,
+                };
+                ladder.insert(index, pair);
+                index
+            }
+        };
+
+        match contract.symbol.option_type() {
+            OptionType::Call => ladder[index].call = Some(contract),
+            OptionType::Put => ladder[index].put = Some(contract),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expirations.is_empty()
+    }
+
+    /// Expiration dates in ascending order
+    pub fn expirations(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.expirations.keys().copied()
+    }
+
+    /// The strike ladder of one expiration, sorted by strike
+    pub fn ladder(&self, expiration: NaiveDate) -> Option<&[StrikePair]> {
+        self.expirations.get(&expiration).map(Vec::as_slice)
+    }
+
+    /// Every contract in the chain, by expiration then strike
+    pub fn contracts(&self) -> impl Iterator<Item = &ChainContract> {
+        self.expirations
+            .values()
+            .flatten()
+            .flat_map(StrikePair::contracts)
+    }
+
+    /// The strike of an expiration closest to the underlying price
+    pub fn at_the_money(
+        &self,
+        expiration: NaiveDate,
+        underlying_price: f64,
+    ) -> Option<&StrikePair> {
+        self.ladder(expiration)?.iter().min_by(|a, b| {
+            let distance = |pair: &StrikePair| (pair.strike - underlying_price).abs();
+            distance(a).total_cmp(&distance(b))
+        })
+    }
+
+    /// Keep only the contracts matching `keep`, dropping emptied strikes and expirations
+    pub fn retain(mut self, mut keep: impl FnMut(&ChainContract) -> bool) -> Self {
+        for ladder in self.expirations.values_mut() {
+            for pair in ladder.iter_mut() {
+                pair.call = pair.call.take().filter(&mut keep);
+                pair.put = pair.put.take().filter(&mut keep);
+            }
+            ladder.retain(|pair| pair.call.is_some() || pair.put.is_some());
+        }
+        self.expirations.retain(|_, ladder| !ladder.is_empty());
+        self
+    }
+
+    /// Keep contracts whose absolute delta is within the range, so puts and calls
+    /// filter alike. Contracts without Greeks are dropped.
+    pub fn filter_delta(self, min: f64, max: f64) -> Self {
+        self.retain(|contract| {
+            contract
+                .delta()
+                .is_some_and(|delta| (min..=max).contains(&delta.abs()))
+        })
+    }
+
+    /// Keep strikes whose ratio to the underlying price is within the range, e.g.
+    /// `0.9` to `1.1` keeps strikes within 10% of the underlying
+    pub fn filter_moneyness(self, underlying_price: f64, min: f64, max: f64) -> Self {
+        self.retain(|contract| (min..=max).contains(&(contract.symbol.strike() / underlying_price)))
+    }
+
+    /// Keep expirations between `min` and `max` calendar days after `as_of`
+    pub fn filter_days_to_expiration(self, as_of: NaiveDate, min: i64, max: i64) -> Self {
+        self.retain(|contract| {
+            let days = (contract.symbol.expiration() - as_of).num_days();
+            (min..=max).contains(&days)
+        })
+    }
+}
+
+impl TryFrom<OptionSnapshots> for OptionChain {
+    type Error = Error;
+
+    fn try_from(snapshots: OptionSnapshots) -> Result<Self, Self::Error> {
+        Self::from_snapshots(snapshots)
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +401,80 @@ mod tests {
         dbg!(&res);
         assert!(res.len() < 11);
     }
+
+    fn sample_chain() -> OptionChain {
+        let snapshot = |delta: f64| {
+            serde_json::from_str(&format!(
+                r#"{{"greeks":{{"delta":{delta},"gamma":0.01,"rho":0.1,"theta":-0.05,"vega":0.2}}}}"#
+            ))
+            .unwrap()
+        };
+        let snapshots: OptionSnapshots = [
+            ("AAPL241220C00190000", snapshot(0.7)),
+            ("AAPL241220P00190000", snapshot(-0.3)),
+            ("AAPL241220C00200000", snapshot(0.5)),
+            ("AAPL241220P00200000", snapshot(-0.5)),
+            ("AAPL241220C00210000", snapshot(0.3)),
+            ("AAPL250117C00200000", snapshot(0.55)),
+        ]
+        .into_iter()
+        .map(|(symbol, snapshot)| (symbol.to_string(), snapshot))
+        .collect();
+
+        OptionChain::from_snapshots(snapshots).unwrap()
+    }
+
+    #[test]
+    fn test_option_chain_ladders() {
+        let chain = sample_chain();
+        let december = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+        let january = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+
+        assert_eq!(
+            chain.expirations().collect::<Vec<_>>(),
+            vec![december, january]
+        );
+        let ladder = chain.ladder(december).unwrap();
+        let strikes: Vec<f64> = ladder.iter().map(|pair| pair.strike).collect();
+        assert_eq!(strikes, vec![190.0, 200.0, 210.0]);
+        assert!(ladder[0].call.is_some() && ladder[0].put.is_some());
+        assert!(ladder[2].put.is_none());
+        assert_eq!(chain.contracts().count(), 6);
+
+        let atm = chain.at_the_money(december, 198.0).unwrap();
+        assert_eq!(atm.strike, 200.0);
+        assert_eq!(
+            atm.call.as_ref().unwrap().moneyness(198.0),
+            Moneyness::OutOfTheMoney
+        );
+        assert_eq!(
+            atm.put.as_ref().unwrap().moneyness(198.0),
+            Moneyness::InTheMoney
+        );
+    }
+
+    #[test]
+    fn test_option_chain_filters() {
+        let december = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+
+        let chain = sample_chain().filter_delta(0.4, 0.6);
+        assert_eq!(chain.contracts().count(), 3);
+        assert_eq!(chain.ladder(december).unwrap().len(), 1);
+
+        let chain = sample_chain().filter_moneyness(200.0, 0.97, 1.03);
+        assert!(chain.contracts().all(|c| c.symbol.strike() == 200.0));
+
+        let as_of = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        let chain = sample_chain().filter_days_to_expiration(as_of, 0, 30);
+        assert_eq!(chain.expirations().collect::<Vec<_>>(), vec![december]);
+
+        assert!(sample_chain().filter_delta(0.9, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_option_chain_rejects_bad_symbols() {
+        let snapshots: OptionSnapshots =
+            [("AAPL".to_string(), serde_json::from_str("{}").unwrap())].into();
+        assert!(OptionChain::from_snapshots(snapshots).is_err());
+    }
 }
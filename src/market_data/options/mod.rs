@@ -12,3 +12,5 @@ pub use snapshot::*;
 
 mod trades;
 pub use trades::*;
+
+pub mod pricing;
@@ -0,0 +1,523 @@
+use super::{Greeks, OptionQuote, OptionSnapshot};
+use crate::{time::new_york_to_utc, OccSymbol, OptionType};
+use chrono::{DateTime, Utc};
+use std::{f64::consts::PI, io::Error};
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 86400.0;
+const BINOMIAL_STEPS: usize = 200;
+
+/// Whether a contract can be exercised before expiration
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExerciseStyle {
+    European,
+    American,
+}
+
+/// Which side of a quote to take the option price from
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuoteSide {
+    Bid,
+    Ask,
+    Mid,
+}
+
+/// Inputs to price a single contract
+///
+/// Rates, dividend yield and volatility are annualised decimals, so 5% is `0.05`,
+/// and time to expiry is in years.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OptionParams {
+    pub option_type: OptionType,
+    pub spot: f64,
+    pub strike: f64,
+    pub time_to_expiry: f64,
+    pub volatility: f64,
+    pub rate: f64,
+    pub dividend_yield: f64,
+}
+
+impl OptionParams {
+    pub fn new(
+        option_type: OptionType,
+        spot: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        volatility: f64,
+    ) -> Self {
+        Self {
+            option_type,
+            spot,
+            strike,
+            time_to_expiry,
+            volatility,
+            rate: 0.0,
+            dividend_yield: 0.0,
+        }
+    }
+
+    /// Params for a listed contract, expiring at the 16:00 New York close of its expiration
+    pub fn for_contract(
+        symbol: &OccSymbol,
+        spot: f64,
+        volatility: f64,
+        as_of: DateTime<Utc>,
+    ) -> Result<Self, Error> {
+        let expiry = new_york_to_utc(symbol.expiration(), "16:00")?;
+        let years = (expiry - as_of).num_seconds().max(0) as f64 / SECONDS_PER_YEAR;
+        Ok(Self::new(
+            symbol.option_type(),
+            spot,
+            symbol.strike(),
+            years,
+            volatility,
+        ))
+    }
+
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn dividend_yield(mut self, dividend_yield: f64) -> Self {
+        self.dividend_yield = dividend_yield;
+        self
+    }
+
+    pub fn volatility(mut self, volatility: f64) -> Self {
+        self.volatility = volatility;
+        self
+    }
+
+    fn intrinsic(&self, spot: f64) -> f64 {
+        match self.option_type {
+            OptionType::Call => (spot - self.strike).max(0.0),
+            OptionType::Put => (self.strike - spot).max(0.0),
+        }
+    }
+
+    /// Pricing collapses to intrinsic value with no time or volatility left
+    fn is_degenerate(&self) -> bool {
+        self.time_to_expiry <= 0.0 || self.volatility <= 0.0
+    }
+
+    fn discount(&self) -> f64 {
+        (-self.rate * self.time_to_expiry).exp()
+    }
+
+    fn dividend_discount(&self) -> f64 {
+        (-self.dividend_yield * self.time_to_expiry).exp()
+    }
+
+    /// Price bounds no arbitrage free European price can leave
+    fn european_bounds(&self) -> (f64, f64) {
+        let spot = self.spot * self.dividend_discount();
+        let strike = self.strike * self.discount();
+        match self.option_type {
+            OptionType::Call => ((spot - strike).max(0.0), spot),
+            OptionType::Put => ((strike - spot).max(0.0), strike),
+        }
+    }
+}
+
+/// Standard normal density
+fn pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// Standard normal distribution, from a Chebyshev fit of erfc accurate to 1.2e-7
+pub(crate) fn cdf(x: f64) -> f64 {
+    let z = x.abs() / 2f64.sqrt();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let tail = 0.5 * t * poly.exp();
+    if x >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+fn d1_d2(params: &OptionParams) -> (f64, f64) {
+    let OptionParams {
+        spot,
+        strike,
+        time_to_expiry: t,
+        volatility: sigma,
+        rate,
+        dividend_yield,
+        ..
+    } = *params;
+    let deviation = sigma * t.sqrt();
+    let d1 = ((spot / strike).ln() + (rate - dividend_yield + sigma * sigma / 2.0) * t) / deviation;
+    (d1, d1 - deviation)
+}
+
+/// Black-Scholes-Merton price of a European contract
+pub fn black_scholes_price(params: &OptionParams) -> f64 {
+    if params.is_degenerate() {
+        let (lower, _) = params.european_bounds();
+        return lower;
+    }
+
+    let (d1, d2) = d1_d2(params);
+    let spot = params.spot * params.dividend_discount();
+    let strike = params.strike * params.discount();
+    match params.option_type {
+        OptionType::Call => spot * cdf(d1) - strike * cdf(d2),
+        OptionType::Put => strike * cdf(-d2) - spot * cdf(-d1),
+    }
+}
+
+/// Black-Scholes-Merton Greeks of a European contract
+///
+/// Theta is per calendar day, vega and rho are per percentage point, matching the
+/// Greeks the alpaca api returns.
+pub fn black_scholes_greeks(params: &OptionParams) -> Greeks {
+    if params.is_degenerate() {
+        let in_the_money = params.intrinsic(params.spot) > 0.0;
+        let delta = match (params.option_type, in_the_money) {
+            (OptionType::Call, true) => 1.0,
+            (OptionType::Put, true) => -1.0,
+            _ => 0.0,
+        };
+        return Greeks {
+            delta,
+            gamma: 0.0,
+            rho: 0.0,
+            theta: 0.0,
+            vega: 0.0,
+        };
+    }
+
+    let (d1, d2) = d1_d2(params);
+    let t = params.time_to_expiry;
+    let sqrt_t = t.sqrt();
+    let dividend_discount = params.dividend_discount();
+    let strike = params.strike * params.discount();
+    let decay = -params.spot * dividend_discount * pdf(d1) * params.volatility / (2.0 * sqrt_t);
+
+    let (delta, theta, rho) = match params.option_type {
+        OptionType::Call => (
+            dividend_discount * cdf(d1),
+            decay - params.rate * strike * cdf(d2)
+                + params.dividend_yield * params.spot * dividend_discount * cdf(d1),
+            strike * t * cdf(d2),
+        ),
+        OptionType::Put => (
+            dividend_discount * (cdf(d1) - 1.0),
+            decay + params.rate * strike * cdf(-d2)
+                - params.dividend_yield * params.spot * dividend_discount * cdf(-d1),
+            -strike * t * cdf(-d2),
+        ),
+    };
+
+    Greeks {
+        delta,
+        gamma: dividend_discount * pdf(d1) / (params.spot * params.volatility * sqrt_t),
+        rho: rho / 100.0,
+        theta: theta / 365.0,
+        vega: params.spot * dividend_discount * pdf(d1) * sqrt_t / 100.0,
+    }
+}
+
+/// Cox-Ross-Rubinstein binomial tree price, supporting early exercise
+pub fn binomial_price(params: &OptionParams, style: ExerciseStyle, steps: usize) -> f64 {
+    if params.is_degenerate() {
+        return match style {
+            ExerciseStyle::American => params.intrinsic(params.spot),
+            ExerciseStyle::European => params.european_bounds().0,
+        };
+    }
+
+    let steps = steps.max(1);
+    let dt = params.time_to_expiry / steps as f64;
+    let up = (params.volatility * dt.sqrt()).exp();
+    let down = 1.0 / up;
+    let growth = ((params.rate - params.dividend_yield) * dt).exp();
+    let probability = ((growth - down) / (up - down)).clamp(0.0, 1.0);
+    let discount = (-params.rate * dt).exp();
+
+    let spot_at = |step: usize, ups: usize| {
+        params.spot * up.powi(ups as i32) * down.powi((step - ups) as i32)
+    };
+    let mut values: Vec<f64> = (0..=steps)
+        .map(|ups| params.intrinsic(spot_at(steps, ups)))
+        .collect();
+
+    for step in (0..steps).rev() {
+        for ups in 0..=step {
+            let held =
+                discount * (probability * values[ups + 1] + (1.0 - probability) * values[ups]);
+            values[ups] = match style {
+                ExerciseStyle::American => held.max(params.intrinsic(spot_at(step, ups))),
+                ExerciseStyle::European => held,
+            };
+        }
+    }
+
+    values[0]
+}
+
+/// Price with Black-Scholes for European contracts and a binomial tree for American
+pub fn theoretical_price(params: &OptionParams, style: ExerciseStyle) -> f64 {
+    match style {
+        ExerciseStyle::European => black_scholes_price(params),
+        ExerciseStyle::American => binomial_price(params, style, BINOMIAL_STEPS),
+    }
+}
+
+/// Greeks for either style, American Greeks are bumped and repriced on the binomial tree
+pub fn greeks(params: &OptionParams, style: ExerciseStyle) -> Greeks {
+    if style == ExerciseStyle::European || params.is_degenerate() {
+        return black_scholes_greeks(params);
+    }
+
+    let price = |params: &OptionParams| binomial_price(params, style, BINOMIAL_STEPS);
+    let base = price(params);
+    let spot_bump = params.spot * 0.01;
+    let up = price(&OptionParams {
+        spot: params.spot + spot_bump,
+        ..*params
+    });
+    let down = price(&OptionParams {
+        spot: params.spot - spot_bump,
+        ..*params
+    });
+    let day = 1.0 / 365.0;
+    let tomorrow = price(&OptionParams {
+        time_to_expiry: (params.time_to_expiry - day).max(0.0),
+        ..*params
+    });
+    // Bumps span one percentage point, matching the units of the European Greeks
+    let bumped = |volatility: f64, rate: f64| {
+        price(&OptionParams {
+            volatility: params.volatility + volatility,
+            rate: params.rate + rate,
+            ..*params
+        })
+    };
+
+    Greeks {
+        delta: (up - down) / (2.0 * spot_bump),
+        gamma: (up - 2.0 * base + down) / (spot_bump * spot_bump),
+        rho: (bumped(0.0, 0.005) - bumped(0.0, -0.005)),
+        theta: tomorrow - base,
+        vega: (bumped(0.005, 0.0) - bumped(-0.005, 0.0)),
+    }
+}
+
+/// Solve for the volatility that reproduces `price`, the volatility in `params` is ignored
+///
+/// Uses Newton's method kept inside a shrinking bracket, falling back to bisection
+/// whenever a Newton step would leave it.
+pub fn implied_volatility(
+    price: f64,
+    params: &OptionParams,
+    style: ExerciseStyle,
+) -> Result<f64, Error> {
+    if !price.is_finite() || params.time_to_expiry <= 0.0 {
+        return Err(Error::other("Implied Volatility Undefined At Expiry"));
+    }
+
+    let error = |volatility: f64| theoretical_price(&params.volatility(volatility), style) - price;
+    let (mut low, mut high) = (1e-6, 10.0);
+    let (low_error, high_error) = (error(low), error(high));
+    if low_error > 0.0 || high_error < 0.0 {
+        return Err(Error::other(format!(
+            "Price {price} Is Outside The No Arbitrage Bounds"
+        )));
+    }
+
+    let mut volatility = 0.3;
+    for _ in 0..100 {
+        let difference = error(volatility);
+        if difference.abs() < 1e-8 {
+            return Ok(volatility);
+        }
+        if difference > 0.0 {
+            high = volatility;
+        } else {
+            low = volatility;
+        }
+        if high - low < 1e-10 {
+            return Ok(volatility);
+        }
+
+        let vega = match style {
+            ExerciseStyle::European => {
+                black_scholes_greeks(&params.volatility(volatility)).vega * 100.0
+            }
+            ExerciseStyle::American => (error(volatility + 1e-4) - difference) / 1e-4,
+        };
+        let newton = volatility - difference / vega;
+        volatility = if vega > 1e-12 && newton > low && newton < high {
+            newton
+        } else {
+            (low + high) / 2.0
+        };
+    }
+
+    Ok(volatility)
+}
+
+/// Implied volatility from one side of a quote or its midpoint
+pub fn implied_volatility_from_quote(
+    quote: &OptionQuote,
+    side: QuoteSide,
+    params: &OptionParams,
+    style: ExerciseStyle,
+) -> Result<f64, Error> {
+    let (bid, ask) = (quote.bp as f64, quote.ap as f64);
+    let price = match side {
+        QuoteSide::Bid => bid,
+        QuoteSide::Ask => ask,
+        QuoteSide::Mid if bid > 0.0 && ask > 0.0 => (bid + ask) / 2.0,
+        QuoteSide::Mid => bid.max(ask),
+    };
+    if price <= 0.0 {
+        return Err(Error::other("Quote Has No Price"));
+    }
+    implied_volatility(price, params, style)
+}
+
+impl OptionSnapshot {
+    /// Compute implied volatility and Greeks locally where the feed left them empty
+    ///
+    /// Implied volatility comes from the quote midpoint, or the latest trade when
+    /// there is no quote. `params` supplies the spot, rate and dividend yield, use
+    /// [`OptionParams::for_contract`] to build it from the snapshot's symbol.
+    pub fn fill_missing(
+        &mut self,
+        params: &OptionParams,
+        style: ExerciseStyle,
+    ) -> Result<(), Error> {
+        let volatility = match self.implied_volatility {
+            Some(volatility) => volatility,
+            None => {
+                let quoted = self.latest_quote.as_ref().map(|quote| {
+                    implied_volatility_from_quote(quote, QuoteSide::Mid, params, style)
+                });
+                let volatility = match (quoted, &self.latest_trade) {
+                    (Some(Ok(volatility)), _) => volatility,
+                    (_, Some(trade)) => implied_volatility(trade.p, params, style)?,
+                    (Some(Err(error)), None) => return Err(error),
+                    (None, None) => return Err(Error::other("Snapshot Has No Price")),
+                };
+                self.implied_volatility = Some(volatility);
+                volatility
+            }
+        };
+
+        if self.greeks.is_none() {
+            self.greeks = Some(greeks(&params.volatility(volatility), style));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at_the_money(option_type: OptionType) -> OptionParams {
+        OptionParams::new(option_type, 100.0, 100.0, 1.0, 0.2).rate(0.05)
+    }
+
+    fn close(value: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (value - expected).abs() < tolerance,
+            "{value} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn test_black_scholes_price() {
+        close(
+            black_scholes_price(&at_the_money(OptionType::Call)),
+            10.4506,
+            1e-4,
+        );
+        close(
+            black_scholes_price(&at_the_money(OptionType::Put)),
+            5.5735,
+            1e-4,
+        );
+
+        let expired = OptionParams::new(OptionType::Call, 110.0, 100.0, 0.0, 0.2);
+        assert_eq!(black_scholes_price(&expired), 10.0);
+    }
+
+    #[test]
+    fn test_black_scholes_greeks() {
+        let greeks = black_scholes_greeks(&at_the_money(OptionType::Call));
+        close(greeks.delta, 0.6368, 1e-4);
+        close(greeks.gamma, 0.018762, 1e-6);
+        close(greeks.vega, 0.375240, 1e-5);
+        close(greeks.theta, -6.414 / 365.0, 1e-5);
+        close(greeks.rho, 0.532325, 1e-5);
+
+        let put = black_scholes_greeks(&at_the_money(OptionType::Put));
+        close(put.delta, 0.6368 - 1.0, 1e-4);
+    }
+
+    #[test]
+    fn test_binomial_price() {
+        let put = at_the_money(OptionType::Put);
+        let european = binomial_price(&put, ExerciseStyle::European, 500);
+        let american = binomial_price(&put, ExerciseStyle::American, 500);
+        close(european, 5.5735, 0.01);
+        close(american, 6.09, 0.01);
+
+        // Without dividends an American call is never exercised early
+        let call = at_the_money(OptionType::Call);
+        close(
+            binomial_price(&call, ExerciseStyle::American, 500),
+            black_scholes_price(&call),
+            0.01,
+        );
+
+        let american_greeks = greeks(&put, ExerciseStyle::American);
+        assert!(american_greeks.delta < black_scholes_greeks(&put).delta);
+    }
+
+    #[test]
+    fn test_implied_volatility() {
+        let params = at_the_money(OptionType::Call).volatility(0.0);
+        let volatility = implied_volatility(10.4506, &params, ExerciseStyle::European).unwrap();
+        close(volatility, 0.2, 1e-4);
+
+        let put = at_the_money(OptionType::Put);
+        let price = binomial_price(
+            &put.volatility(0.35),
+            ExerciseStyle::American,
+            BINOMIAL_STEPS,
+        );
+        let volatility = implied_volatility(price, &put, ExerciseStyle::American).unwrap();
+        close(volatility, 0.35, 1e-4);
+
+        assert!(implied_volatility(0.1, &params, ExerciseStyle::European).is_err());
+        assert!(implied_volatility(150.0, &params, ExerciseStyle::European).is_err());
+    }
+
+    #[test]
+    fn test_fill_missing_snapshot() {
+        let mut snapshot: OptionSnapshot = serde_json::from_str(
+            r#"{"latestQuote":{"t":"2024-09-20T14:28:58Z","ax":"C","ap":10.55,"as":1,"bx":"C","bp":10.35,"bs":1,"c":"A"}}"#,
+        )
+        .unwrap();
+        let params = at_the_money(OptionType::Call);
+        snapshot
+            .fill_missing(&params, ExerciseStyle::European)
+            .unwrap();
+
+        close(snapshot.implied_volatility.unwrap(), 0.2, 1e-3);
+        close(snapshot.greeks.unwrap().delta, 0.6368, 1e-3);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OptionQuote {
     pub t: String,  // Timestamp
     pub ax: String, // Exchange
@@ -16,7 +16,7 @@ pub struct OptionQuote {
 
 pub type LatestOptionQuotes = HashMap<String, OptionQuote>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestOptionQuotesResponse {
     quotes: LatestOptionQuotes,
 }
@@ -28,10 +28,10 @@ pub struct LatestOptionQuotesQuery<'a> {
 }
 
 impl<'a> LatestOptionQuotesQuery<'a> {
-    pub fn new(symbols: Vec<&'a str>) -> Self {
+    pub fn new<S: AsRef<str> + ?Sized>(symbols: Vec<&'a S>) -> Self {
         Self {
             url: "https://data.alpaca.markets/v1beta1/options/quotes/latest",
-            symbols,
+            symbols: symbols.into_iter().map(AsRef::as_ref).collect(),
             feed: None,
         }
     }
@@ -71,4 +71,11 @@ mod tests {
         dbg!(&res);
         assert!(res.contains_key("AAPL241220C00300000"));
     }
+
+    #[test]
+    fn test_latest_option_quotes_query_accepts_occ_symbols() {
+        let symbol: crate::OccSymbol = "AAPL241220C00300000".parse().unwrap();
+        let query = LatestOptionQuotesQuery::new(vec![&symbol]);
+        assert!(query.build().ends_with("?symbols=AAPL241220C00300000"));
+    }
 }
@@ -1,9 +1,9 @@
 use super::{quotes::OptionQuote, trades::OptionTrade};
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OptionSnapshot {
     pub greeks: Option<Greeks>,
@@ -12,7 +12,7 @@ pub struct OptionSnapshot {
     pub implied_volatility: Option<f64>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
 pub struct Greeks {
     pub delta: f64,
     pub gamma: f64,
@@ -23,7 +23,7 @@ pub struct Greeks {
 
 pub type OptionSnapshots = HashMap<String, OptionSnapshot>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OptionSnapshotResponse {
     pub snapshots: OptionSnapshots,
     pub next_page_token: Option<String>,
@@ -38,10 +38,10 @@ pub struct OptionSnapshotQuery<'a> {
 }
 
 impl<'a> OptionSnapshotQuery<'a> {
-    pub fn new(symbols: Vec<&'a str>) -> Self {
+    pub fn new<S: AsRef<str> + ?Sized>(symbols: Vec<&'a S>) -> Self {
         Self {
             url: "https://data.alpaca.markets/v1beta1/options/snapshots",
-            symbols,
+            symbols: symbols.into_iter().map(AsRef::as_ref).collect(),
             feed: None,
             updated_since: None,
             limit: None,
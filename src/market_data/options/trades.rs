@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OptionTrade {
     pub t: String, // Timestamp
     pub x: String, // Exchange
@@ -14,13 +14,13 @@ pub struct OptionTrade {
 pub type HistoricalOptionTrades = HashMap<String, Vec<OptionTrade>>;
 pub type LatestOptionTrades = HashMap<String, OptionTrade>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalOptionTradesResponse {
     trades: HistoricalOptionTrades,
     next_page_token: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestOptionTradesResponse {
     trades: LatestOptionTrades,
 }
@@ -42,10 +42,10 @@ pub struct LatestOptionTradesQuery<'a> {
 }
 
 impl<'a> HistoricalOptionTradesQuery<'a> {
-    pub fn new(symbols: Vec<&'a str>) -> Self {
+    pub fn new<S: AsRef<str> + ?Sized>(symbols: Vec<&'a S>) -> Self {
         Self {
             url: "https://data.alpaca.markets/v1beta1/options/trades",
-            symbols,
+            symbols: symbols.into_iter().map(AsRef::as_ref).collect(),
             start: None,
             end: None,
             limit: None,
@@ -147,10 +147,10 @@ impl<'a> HistoricalOptionTradesQuery<'a> {
 }
 
 impl<'a> LatestOptionTradesQuery<'a> {
-    pub fn new(symbols: Vec<&'a str>) -> Self {
+    pub fn new<S: AsRef<str> + ?Sized>(symbols: Vec<&'a S>) -> Self {
         Self {
             url: "https://data.alpaca.markets/v1beta1/options/trades/latest",
-            symbols,
+            symbols: symbols.into_iter().map(AsRef::as_ref).collect(),
             feed: None,
         }
     }
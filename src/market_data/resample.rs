@@ -0,0 +1,602 @@
+use crate::{
+    market_data::{
+        crypto::CryptoTrade, options::OptionTrade, stocks::StockBar, stocks::StockTrade,
+    },
+    time::{format_timestamp, new_york_offset, new_york_to_utc, parse_date, parse_timestamp},
+    trading::calendar::CalendarDay,
+    TimeFrame, TimeFrameUnit,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use std::{io::Error, str::FromStr};
+
+/// Length of a resampled bar, not limited to the timeframes Alpaca serves
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BarPeriod {
+    Minutes(u32),
+    Hours(u32),
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+}
+
+impl From<TimeFrame> for BarPeriod {
+    fn from(timeframe: TimeFrame) -> Self {
+        let amount = timeframe.amount();
+        match timeframe.unit() {
+            TimeFrameUnit::Minute => BarPeriod::Minutes(amount),
+            TimeFrameUnit::Hour => BarPeriod::Hours(amount),
+            TimeFrameUnit::Day => BarPeriod::Days(amount),
+            TimeFrameUnit::Week => BarPeriod::Weeks(amount),
+            TimeFrameUnit::Month => BarPeriod::Months(amount),
+        }
+    }
+}
+
+/// Parses the same notation as [`TimeFrame`], e.g. "3Min", "2H", "1Week" or "6M"
+impl FromStr for BarPeriod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let amount: u32 = amount
+            .parse()
+            .ok()
+            .filter(|amount| *amount > 0)
+            .ok_or_else(|| Error::other("Invalid Bar Period Amount"))?;
+
+        match unit {
+            "Min" | "T" => Ok(BarPeriod::Minutes(amount)),
+            "Hour" | "H" => Ok(BarPeriod::Hours(amount)),
+            "Day" | "D" => Ok(BarPeriod::Days(amount)),
+            "Week" | "W" => Ok(BarPeriod::Weeks(amount)),
+            "Month" | "M" => Ok(BarPeriod::Months(amount)),
+            _ => Err(Error::other("Invalid Bar Period Unit")),
+        }
+    }
+}
+
+impl BarPeriod {
+    fn intraday_seconds(&self) -> Option<i64> {
+        match *self {
+            BarPeriod::Minutes(n) => Some(n as i64 * 60),
+            BarPeriod::Hours(n) => Some(n as i64 * 3600),
+            _ => None,
+        }
+    }
+
+    /// Index of the calendar bucket a date falls in, for day based periods
+    fn date_bucket(&self, date: NaiveDate) -> i64 {
+        // 1970-01-01 was a Thursday, shifting by 3 makes weeks start on Monday
+        let days = date.signed_duration_since(NaiveDate::default()).num_days();
+        match *self {
+            BarPeriod::Days(n) => days.div_euclid(n as i64),
+            BarPeriod::Weeks(n) => (days + 3).div_euclid(7).div_euclid(n as i64),
+            BarPeriod::Months(n) => {
+                (date.year() as i64 * 12 + date.month0() as i64).div_euclid(n as i64)
+            }
+            BarPeriod::Minutes(_) | BarPeriod::Hours(_) => days,
+        }
+    }
+
+    /// First date of a calendar bucket, the inverse of `date_bucket`
+    fn bucket_date(&self, bucket: i64) -> NaiveDate {
+        let from_days = |days: i64| NaiveDate::default() + Duration::days(days);
+        match *self {
+            BarPeriod::Days(n) => from_days(bucket * n as i64),
+            BarPeriod::Weeks(n) => from_days(bucket * n as i64 * 7 - 3),
+            BarPeriod::Months(n) => {
+                let months = bucket * n as i64;
+                NaiveDate::from_ymd_opt(
+                    months.div_euclid(12) as i32,
+                    months.rem_euclid(12) as u32 + 1,
+                    1,
+                )
+                .expect("Month index is in range")
+            }
+            BarPeriod::Minutes(_) | BarPeriod::Hours(_) => from_days(bucket),
+        }
+    }
+
+    /// Start of the bucket holding `time`, buckets are aligned to midnight UTC
+    fn bucket_start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.intraday_seconds() {
+            Some(seconds) => {
+                let start = time.timestamp().div_euclid(seconds) * seconds;
+                Utc.timestamp_opt(start, 0).single().unwrap_or(time)
+            }
+            None => self
+                .bucket_date(self.date_bucket(time.date_naive()))
+                .and_hms_opt(0, 0, 0)
+                .expect("Midnight is valid")
+                .and_utc(),
+        }
+    }
+}
+
+/// Running totals while a bar is built, VWAP is tracked as traded notional
+struct BarAccumulator {
+    t: String,
+    o: f64,
+    h: f64,
+    l: f64,
+    c: f64,
+    v: f64,
+    n: i64,
+    notional: f64,
+}
+
+impl BarAccumulator {
+    fn new(t: String, open: f64) -> Self {
+        Self {
+            t,
+            o: open,
+            h: f64::MIN,
+            l: f64::MAX,
+            c: open,
+            v: 0.0,
+            n: 0,
+            notional: 0.0,
+        }
+    }
+
+    fn add_bar(&mut self, bar: &StockBar) {
+        self.h = self.h.max(bar.h as f64);
+        self.l = self.l.min(bar.l as f64);
+        self.c = bar.c as f64;
+        self.v += bar.v as f64;
+        self.n += bar.n as i64;
+        self.notional += bar.vw as f64 * bar.v as f64;
+    }
+
+    fn add_tick(&mut self, price: f64, size: f64) {
+        self.h = self.h.max(price);
+        self.l = self.l.min(price);
+        self.c = price;
+        self.v += size;
+        self.n += 1;
+        self.notional += price * size;
+    }
+
+    fn finish(self) -> StockBar {
+        let vw = if self.v > 0.0 {
+            self.notional / self.v
+        } else {
+            self.c
+        };
+        StockBar {
+            t: self.t,
+            o: self.o as f32,
+            h: self.h as f32,
+            l: self.l as f32,
+            c: self.c as f32,
+            v: self.v as f32,
+            n: self.n as i32,
+            vw: vw as f32,
+        }
+    }
+}
+
+/// Merge bars sharing a key into one bar each, input must be in time order
+fn merge_bars<'a, K: PartialEq>(
+    bars: impl Iterator<Item = (K, String, &'a StockBar)>,
+) -> Vec<StockBar> {
+    let mut merged = Vec::new();
+    let mut current: Option<(K, BarAccumulator)> = None;
+
+    for (key, start, bar) in bars {
+        match &mut current {
+            Some((current_key, accumulator)) if *current_key == key => accumulator.add_bar(bar),
+            _ => {
+                if let Some((_, accumulator)) = current.take() {
+                    merged.push(accumulator.finish());
+                }
+                let mut accumulator = BarAccumulator::new(start, bar.o as f64);
+                accumulator.add_bar(bar);
+                current = Some((key, accumulator));
+            }
+        }
+    }
+
+    if let Some((_, accumulator)) = current {
+        merged.push(accumulator.finish());
+    }
+    merged
+}
+
+fn sorted_by_time(bars: &[StockBar]) -> Result<Vec<(DateTime<Utc>, &StockBar)>, Error> {
+    let mut timed = bars
+        .iter()
+        .map(|bar| Ok((parse_timestamp(&bar.t)?, bar)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    timed.sort_by_key(|(time, _)| *time);
+    Ok(timed)
+}
+
+/// Resample bars to a coarser period, aligned to midnight UTC and Monday for weeks
+///
+/// Volume and trade counts are summed and VWAP is re-weighted by volume.
+pub fn resample_bars(bars: &[StockBar], period: BarPeriod) -> Result<Vec<StockBar>, Error> {
+    let timed = sorted_by_time(bars)?;
+    Ok(merge_bars(timed.into_iter().map(|(time, bar)| {
+        let start = period.bucket_start(time);
+        (start, format_timestamp(start), bar)
+    })))
+}
+
+/// Resample bars to a coarser period, aligned to the market sessions of a calendar
+///
+/// Intraday buckets start at each session's open and never cross its close, and bars
+/// outside every session are dropped. Longer buckets take every bar on a session's New
+/// York date, `Days(n)` groups every `n` trading sessions while weeks and months are
+/// calendar buckets. They are stamped at midnight New York time on their first session
+/// like Alpaca's own daily bars, so those can be resampled too.
+pub fn resample_bars_in_sessions(
+    bars: &[StockBar],
+    period: BarPeriod,
+    calendar: &[CalendarDay],
+) -> Result<Vec<StockBar>, Error> {
+    let mut sessions = calendar
+        .iter()
+        .map(|day| Ok((parse_date(&day.date)?, day.open_at()?, day.close_at()?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    sessions.sort_by_key(|(date, _, _)| *date);
+
+    let midnight = |date: NaiveDate| {
+        new_york_to_utc(date, "00:00")
+            .map(format_timestamp)
+            .unwrap_or_default()
+    };
+
+    let mut keyed = Vec::new();
+    let mut last: Option<((i64, i64), String)> = None;
+    for (time, bar) in sorted_by_time(bars)? {
+        let key = match period.intraday_seconds() {
+            Some(seconds) => {
+                let index = sessions.partition_point(|(_, open, _)| *open <= time);
+                let Some(session) = index.checked_sub(1) else {
+                    continue;
+                };
+                let (_, open, close) = sessions[session];
+                if time >= close {
+                    continue;
+                }
+                let offset = (time - open).num_seconds().div_euclid(seconds) * seconds;
+                let start = open + Duration::seconds(offset);
+                ((session as i64, offset), format_timestamp(start))
+            }
+            // Daily bars are stamped at midnight New York, before the session opens,
+            // so longer buckets match bars by date instead
+            None => {
+                let date = time
+                    .with_timezone(&new_york_offset(time.date_naive()))
+                    .date_naive();
+                let Ok(session) = sessions.binary_search_by_key(&date, |(date, _, _)| *date) else {
+                    continue;
+                };
+                let bucket = match period {
+                    BarPeriod::Days(n) => session as i64 / n as i64,
+                    _ => period.date_bucket(date),
+                };
+                // Stamped with the first session date in the bucket
+                match &last {
+                    Some((key, start)) if *key == (bucket, 0) => (*key, start.clone()),
+                    _ => ((bucket, 0), midnight(date)),
+                }
+            }
+        };
+        last = Some(key.clone());
+        keyed.push((key.0, key.1, bar));
+    }
+
+    Ok(merge_bars(keyed.into_iter()))
+}
+
+/// A single trade print that bars can be built from
+pub trait Tick {
+    fn timestamp(&self) -> &str;
+    fn price(&self) -> f64;
+    fn size(&self) -> f64;
+}
+
+impl Tick for StockTrade {
+    fn timestamp(&self) -> &str {
+        &self.t
+    }
+
+    fn price(&self) -> f64 {
+        self.p
+    }
+
+    fn size(&self) -> f64 {
+        self.s as f64
+    }
+}
+
+impl Tick for CryptoTrade {
+    fn timestamp(&self) -> &str {
+        &self.t
+    }
+
+    fn price(&self) -> f64 {
+        self.p
+    }
+
+    fn size(&self) -> f64 {
+        self.s
+    }
+}
+
+impl Tick for OptionTrade {
+    fn timestamp(&self) -> &str {
+        &self.t
+    }
+
+    fn price(&self) -> f64 {
+        self.p
+    }
+
+    fn size(&self) -> f64 {
+        self.s as f64
+    }
+}
+
+/// Build time bars from trades, buckets are aligned like [`resample_bars`]
+pub fn time_bars<T: Tick>(trades: &[T], period: BarPeriod) -> Result<Vec<StockBar>, Error> {
+    let mut timed = trades
+        .iter()
+        .map(|trade| Ok((parse_timestamp(trade.timestamp())?, trade)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    timed.sort_by_key(|(time, _)| *time);
+
+    let mut bars = Vec::new();
+    let mut current: Option<(DateTime<Utc>, BarAccumulator)> = None;
+    for (time, trade) in timed {
+        let start = period.bucket_start(time);
+        match &mut current {
+            Some((current_start, accumulator)) if *current_start == start => {
+                accumulator.add_tick(trade.price(), trade.size())
+            }
+            _ => {
+                if let Some((_, accumulator)) = current.take() {
+                    bars.push(accumulator.finish());
+                }
+                let mut accumulator = BarAccumulator::new(format_timestamp(start), trade.price());
+                accumulator.add_tick(trade.price(), trade.size());
+                current = Some((start, accumulator));
+            }
+        }
+    }
+
+    if let Some((_, accumulator)) = current {
+        bars.push(accumulator.finish());
+    }
+    Ok(bars)
+}
+
+/// Close a bar whenever `is_full` says so, bars are stamped with their first trade.
+/// Trades are sorted by time first, like `time_bars`.
+fn threshold_bars<T: Tick>(
+    trades: &[T],
+    is_full: impl Fn(&BarAccumulator) -> bool,
+) -> Result<Vec<StockBar>, Error> {
+    let mut timed = trades
+        .iter()
+        .map(|trade| Ok((parse_timestamp(trade.timestamp())?, trade)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    timed.sort_by_key(|(time, _)| *time);
+
+    let mut bars = Vec::new();
+    let mut current: Option<BarAccumulator> = None;
+
+    for (_, trade) in timed {
+        let accumulator = current.get_or_insert_with(|| {
+            BarAccumulator::new(trade.timestamp().to_string(), trade.price())
+        });
+        accumulator.add_tick(trade.price(), trade.size());
+
+        if is_full(accumulator) {
+            bars.extend(current.take().map(BarAccumulator::finish));
+        }
+    }
+
+    bars.extend(current.map(BarAccumulator::finish));
+    Ok(bars)
+}
+
+/// Build a bar every `ticks` trades, the last bar may hold fewer
+pub fn tick_bars<T: Tick>(trades: &[T], ticks: usize) -> Result<Vec<StockBar>, Error> {
+    let ticks = ticks.max(1) as i64;
+    threshold_bars(trades, |bar| bar.n >= ticks)
+}
+
+/// Build a bar each time traded volume reaches `volume`
+pub fn volume_bars<T: Tick>(trades: &[T], volume: f64) -> Result<Vec<StockBar>, Error> {
+    threshold_bars(trades, |bar| bar.v >= volume)
+}
+
+/// Build a bar each time traded notional (price times size) reaches `dollars`
+pub fn dollar_bars<T: Tick>(trades: &[T], dollars: f64) -> Result<Vec<StockBar>, Error> {
+    threshold_bars(trades, |bar| bar.notional >= dollars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(t: &str, o: f32, h: f32, l: f32, c: f32, v: f32, vw: f32) -> StockBar {
+        StockBar {
+            t: t.to_string(),
+            o,
+            h,
+            l,
+            c,
+            v,
+            n: 10,
+            vw,
+        }
+    }
+
+    fn trade(t: &str, p: f64, s: i32) -> StockTrade {
+        StockTrade {
+            t: t.to_string(),
+            x: "V".to_string(),
+            p,
+            s,
+            c: vec!["@".to_string()],
+            i: 1,
+            z: "C".to_string(),
+        }
+    }
+
+    fn minute_bars() -> Vec<StockBar> {
+        vec![
+            bar("2024-07-01T13:30:00Z", 10.0, 11.0, 9.5, 10.5, 100.0, 10.2),
+            bar("2024-07-01T13:31:00Z", 10.5, 12.0, 10.0, 11.5, 300.0, 11.0),
+            bar("2024-07-01T13:32:00Z", 11.5, 11.8, 11.0, 11.2, 100.0, 11.4),
+            bar("2024-07-01T13:33:00Z", 11.2, 11.3, 10.8, 11.0, 200.0, 11.1),
+        ]
+    }
+
+    #[test]
+    fn test_bar_period_from_str() {
+        assert_eq!("3Min".parse::<BarPeriod>().unwrap(), BarPeriod::Minutes(3));
+        assert_eq!("2H".parse::<BarPeriod>().unwrap(), BarPeriod::Hours(2));
+        assert_eq!("1W".parse::<BarPeriod>().unwrap(), BarPeriod::Weeks(1));
+        assert_eq!("6Month".parse::<BarPeriod>().unwrap(), BarPeriod::Months(6));
+        assert!("0Min".parse::<BarPeriod>().is_err());
+        assert!("5Sec".parse::<BarPeriod>().is_err());
+    }
+
+    #[test]
+    fn test_resample_minutes() {
+        let bars = resample_bars(&minute_bars(), BarPeriod::Minutes(3)).unwrap();
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].t, "2024-07-01T13:30:00Z");
+        assert_eq!(bars[0].o, 10.0);
+        assert_eq!(bars[0].h, 12.0);
+        assert_eq!(bars[0].l, 9.5);
+        assert_eq!(bars[0].c, 11.2);
+        assert_eq!(bars[0].v, 500.0);
+        assert_eq!(bars[0].n, 30);
+        // (10.2 * 100 + 11.0 * 300 + 11.4 * 100) / 500
+        assert!((bars[0].vw - 10.92).abs() < 1e-4);
+        assert_eq!(bars[1].t, "2024-07-01T13:33:00Z");
+    }
+
+    #[test]
+    fn test_resample_weeks_start_on_monday() {
+        let bars = vec![
+            bar("2024-07-05T04:00:00Z", 1.0, 1.0, 1.0, 1.0, 1.0, 1.0),
+            bar("2024-07-08T04:00:00Z", 2.0, 2.0, 2.0, 2.0, 1.0, 2.0),
+            bar("2024-07-12T04:00:00Z", 3.0, 3.0, 3.0, 3.0, 1.0, 3.0),
+        ];
+        let weekly = resample_bars(&bars, BarPeriod::Weeks(1)).unwrap();
+
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly[0].t, "2024-07-01T00:00:00Z");
+        assert_eq!(weekly[1].t, "2024-07-08T00:00:00Z");
+        assert_eq!(weekly[1].c, 3.0);
+
+        let quarterly = resample_bars(&bars, BarPeriod::Months(3)).unwrap();
+        assert_eq!(quarterly.len(), 1);
+        assert_eq!(quarterly[0].t, "2024-07-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_resample_in_sessions() {
+        let calendar = vec![CalendarDay {
+            date: "2024-07-01".to_string(),
+            open: "09:30".to_string(),
+            close: "16:00".to_string(),
+            settlement_date: "2024-07-02".to_string(),
+        }];
+        let mut bars = vec![bar("2024-07-01T13:29:00Z", 9.0, 9.0, 9.0, 9.0, 50.0, 9.0)];
+        bars.extend(minute_bars());
+
+        let hourly = resample_bars_in_sessions(&bars, BarPeriod::Hours(1), &calendar).unwrap();
+        assert_eq!(hourly.len(), 1);
+        assert_eq!(hourly[0].t, "2024-07-01T13:30:00Z");
+        assert_eq!(hourly[0].o, 10.0);
+        assert_eq!(hourly[0].v, 700.0);
+
+        let daily = resample_bars_in_sessions(&bars, BarPeriod::Days(1), &calendar).unwrap();
+        assert_eq!(daily[0].t, "2024-07-01T04:00:00Z");
+    }
+
+    #[test]
+    fn test_resample_daily_bars_in_sessions() {
+        let session = |date: &str| CalendarDay {
+            date: date.to_string(),
+            open: "09:30".to_string(),
+            close: "16:00".to_string(),
+            settlement_date: date.to_string(),
+        };
+        let calendar = vec![
+            session("2024-07-03"),
+            session("2024-07-05"),
+            session("2024-07-08"),
+        ];
+        // Alpaca stamps daily bars at midnight New York time
+        let bars = vec![
+            bar("2024-07-03T04:00:00Z", 1.0, 1.5, 1.0, 1.2, 1.0, 1.0),
+            bar("2024-07-05T04:00:00Z", 2.0, 2.0, 2.0, 2.0, 1.0, 2.0),
+            bar("2024-07-08T04:00:00Z", 3.0, 3.0, 3.0, 3.0, 1.0, 3.0),
+        ];
+
+        let weekly = resample_bars_in_sessions(&bars, BarPeriod::Weeks(1), &calendar).unwrap();
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly[0].t, "2024-07-03T04:00:00Z");
+        assert_eq!(weekly[0].o, 1.0);
+        assert_eq!(weekly[0].c, 2.0);
+        assert_eq!(weekly[0].v, 2.0);
+        assert_eq!(weekly[1].t, "2024-07-08T04:00:00Z");
+
+        let two_day = resample_bars_in_sessions(&bars, BarPeriod::Days(2), &calendar).unwrap();
+        assert_eq!(two_day.len(), 2);
+        assert_eq!(two_day[0].c, 2.0);
+        assert_eq!(two_day[1].c, 3.0);
+    }
+
+    #[test]
+    fn test_time_bars_from_trades() {
+        let trades = vec![
+            trade("2024-07-01T13:30:05Z", 10.0, 100),
+            trade("2024-07-01T13:30:45Z", 11.0, 100),
+            trade("2024-07-01T13:31:10Z", 12.0, 50),
+        ];
+        let bars = time_bars(&trades, BarPeriod::Minutes(1)).unwrap();
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].t, "2024-07-01T13:30:00Z");
+        assert_eq!(bars[0].vw, 10.5);
+        assert_eq!(bars[0].n, 2);
+        assert_eq!(bars[1].c, 12.0);
+    }
+
+    #[test]
+    fn test_threshold_bars_from_trades() {
+        // Out of order, the feed can deliver late prints
+        let trades = vec![
+            trade("2024-07-01T13:30:45Z", 11.0, 100),
+            trade("2024-07-01T13:30:05Z", 10.0, 100),
+            trade("2024-07-01T13:31:10Z", 12.0, 50),
+        ];
+
+        let ticks = tick_bars(&trades, 2).unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].t, "2024-07-01T13:30:05Z");
+        assert_eq!(ticks[0].o, 10.0);
+        assert_eq!(ticks[1].t, "2024-07-01T13:31:10Z");
+
+        let volume = volume_bars(&trades, 150.0).unwrap();
+        assert_eq!(volume.len(), 2);
+        assert_eq!(volume[0].v, 200.0);
+
+        let dollars = dollar_bars(&trades, 1500.0).unwrap();
+        assert_eq!(dollars.len(), 2);
+        assert_eq!(dollars[0].c, 11.0);
+        assert_eq!(dollars[1].v, 50.0);
+    }
+}
@@ -1,14 +1,14 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ActiveStock {
     pub symbol: String,
     pub volume: u64,
     pub trade_count: u64,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ActiveStocksResponse {
     pub most_actives: Vec<ActiveStock>,
     pub last_updated: String,
@@ -59,7 +59,7 @@ impl<'a> ActiveStocksQuery<'a> {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TopMover {
     pub symbol: String,
     pub percent_change: f64,
@@ -67,7 +67,7 @@ pub struct TopMover {
     pub price: f64,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TopMoverResponse {
     pub gainers: Vec<TopMover>,
     pub losers: Vec<TopMover>,
@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct StockAuction {
     pub d: String,
     pub o: Option<Vec<StockPrice>>, // Open
@@ -10,7 +10,7 @@ pub struct StockAuction {
     pub v: Option<Vec<StockPrice>>, // Volume
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct StockPrice {
     pub c: String, // Condition
     pub p: f32,    // Price
@@ -21,7 +21,7 @@ pub struct StockPrice {
 
 type HistoricalAuctions = HashMap<String, Vec<StockAuction>>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalAuctionResponse {
     pub auctions: HistoricalAuctions,
     pub next_page_token: Option<String>,
@@ -1,9 +1,9 @@
-use crate::{request, TimeFrame};
-use serde::Deserialize;
+use crate::{market_data::adjustment::Adjustment, request, TimeFrame};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// API object for a Bar
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StockBar {
     pub t: String, // Timestamp
     pub o: f32,    // Open
@@ -21,13 +21,13 @@ pub type HistoricalBars = HashMap<String, Vec<StockBar>>;
 /// A custom type for a hashmap of stock symbols and latest bars
 pub type LatestBars = HashMap<String, StockBar>;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct HistoricalBarsResponse {
     pub bars: HistoricalBars,
     pub next_page_token: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct LatestBarsResponse {
     pub bars: LatestBars,
 }
@@ -36,6 +36,7 @@ pub struct HistoricalBarsQuery<'a> {
     url: &'a str,
     symbols: Vec<&'a str>,
     timeframe: TimeFrame,
+    adjustment: Option<Adjustment>,
     start: Option<&'a str>,
     end: Option<&'a str>,
     feed: Option<&'a str>,
@@ -59,6 +60,7 @@ impl<'a> HistoricalBarsQuery<'a> {
             url: "https://data.alpaca.markets/v2/stocks/bars",
             symbols,
             timeframe,
+            adjustment: None,
             start: None,
             end: None,
             feed: None,
@@ -70,6 +72,12 @@ impl<'a> HistoricalBarsQuery<'a> {
         }
     }
 
+    /// How bars are adjusted for corporate actions, the API defaults to raw
+    pub fn adjustment(mut self, adjustment: Adjustment) -> Self {
+        self.adjustment = Some(adjustment);
+        self
+    }
+
     pub fn start(mut self, start: &'a str) -> Self {
         self.start = Some(start);
         self
@@ -116,8 +124,11 @@ impl<'a> HistoricalBarsQuery<'a> {
         let mut query = format!(
             "symbols={}&timeframe={}",
             self.symbols.join(","),
-            self.timeframe.to_string()
+            self.timeframe
         );
+        if let Some(adjustment) = self.adjustment {
+            query.push_str(&format!("&adjustment={adjustment}"));
+        }
         if let Some(start) = self.start {
             query.push_str(&format!("&start={start}"));
         }
@@ -241,6 +252,15 @@ mod tests {
         assert!(res.contains_key("AAPL"));
     }
 
+    #[test]
+    fn test_historical_bar_query_adjustment() {
+        let route = HistoricalBarsQuery::new(vec!["AAPL"], TimeFrame::OneDay)
+            .adjustment(Adjustment::All)
+            .build();
+
+        assert!(route.ends_with("symbols=AAPL&timeframe=1Day&adjustment=all"));
+    }
+
     #[test]
     fn test_latest_bar_query() {
         let res = LatestBarsQuery::new(vec!["AAPL", "TSLA"])
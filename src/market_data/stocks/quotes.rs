@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct StockQuote {
     pub t: String,      // Timestamp
     pub ax: String,     // Exchange
@@ -15,16 +15,16 @@ pub struct StockQuote {
     pub z: String,      // Condition
 }
 
-type HistoricalQuotes = HashMap<String, Vec<StockQuote>>;
-type LatestQuotes = HashMap<String, StockQuote>;
+pub type HistoricalQuotes = HashMap<String, Vec<StockQuote>>;
+pub type LatestQuotes = HashMap<String, StockQuote>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalQuotesResponse {
     quotes: HistoricalQuotes,
     next_page_token: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestQuotesResponse {
     quotes: LatestQuotes,
 }
@@ -1,9 +1,9 @@
 use super::{bars::StockBar, quotes::StockQuote, trades::StockTrade};
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct StockSnapshot {
     pub latest_trade: Option<StockTrade>,
@@ -1,8 +1,8 @@
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct StockTrade {
     pub t: String,      // Timestamp
     pub x: String,      // Exchange
@@ -16,13 +16,13 @@ pub struct StockTrade {
 pub type HistoricalTrades = HashMap<String, Vec<StockTrade>>;
 pub type LatestTrades = HashMap<String, StockTrade>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HistoricalTradesResponse {
     trades: HistoricalTrades,
     next_page_token: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LatestTradesResponse {
     trades: LatestTrades,
 }
@@ -0,0 +1,241 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, io::Error, str::FromStr};
+
+/// Call or put side of an option contract
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    fn code(&self) -> char {
+        match self {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        }
+    }
+}
+
+/// Formats as the alpaca api names it, "call" or "put"
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionType::Call => write!(f, "call"),
+            OptionType::Put => write!(f, "put"),
+        }
+    }
+}
+
+impl FromStr for OptionType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "call" | "Call" | "C" => Ok(OptionType::Call),
+            "put" | "Put" | "P" => Ok(OptionType::Put),
+            _ => Err(Error::other(format!("Invalid Option Type: {s}"))),
+        }
+    }
+}
+
+/// An OCC option symbol such as `AAPL240920C00105000`
+///
+/// Made of the root symbol, the expiration as `YYMMDD`, `C` or `P`, and the strike
+/// in thousandths of a dollar padded to 8 digits. Parsing also accepts the 21
+/// character form with the root padded by spaces.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct OccSymbol {
+    symbol: String,
+    root: String,
+    expiration: NaiveDate,
+    option_type: OptionType,
+    strike_thousandths: u32,
+}
+
+impl OccSymbol {
+    pub fn new(
+        root: &str,
+        expiration: NaiveDate,
+        option_type: OptionType,
+        strike: f64,
+    ) -> Result<Self, Error> {
+        let thousandths = (strike * 1000.0).round();
+        if !strike.is_finite()
+            || strike <= 0.0
+            || thousandths > 99_999_999.0
+            || (strike * 1000.0 - thousandths).abs() > 1e-6
+        {
+            return Err(Error::other(format!("Invalid Strike Price: {strike}")));
+        }
+        Self::from_parts(root, expiration, option_type, thousandths as u32)
+    }
+
+    fn from_parts(
+        root: &str,
+        expiration: NaiveDate,
+        option_type: OptionType,
+        strike_thousandths: u32,
+    ) -> Result<Self, Error> {
+        if root.is_empty()
+            || root.len() > 6
+            || !root
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(Error::other(format!("Invalid Root Symbol: {root}")));
+        }
+        if !(2000..2100).contains(&expiration.year()) {
+            return Err(Error::other(format!(
+                "Invalid Expiration Date: {expiration}"
+            )));
+        }
+
+        let symbol = format!(
+            "{root}{}{}{strike_thousandths:08}",
+            expiration.format("%y%m%d"),
+            option_type.code()
+        );
+        Ok(Self {
+            symbol,
+            root: root.to_string(),
+            expiration,
+            option_type,
+            strike_thousandths,
+        })
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub fn expiration(&self) -> NaiveDate {
+        self.expiration
+    }
+
+    pub fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    pub fn strike(&self) -> f64 {
+        self.strike_thousandths as f64 / 1000.0
+    }
+
+    /// The symbol with no padding as the alpaca api uses it
+    pub fn as_str(&self) -> &str {
+        &self.symbol
+    }
+
+    /// The standard 21 character form with the root padded to 6 characters
+    pub fn padded(&self) -> String {
+        format!("{:<6}{}", self.root, &self.symbol[self.root.len()..])
+    }
+}
+
+impl AsRef<str> for OccSymbol {
+    fn as_ref(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl fmt::Display for OccSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.symbol)
+    }
+}
+
+impl FromStr for OccSymbol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::other(format!("Invalid OCC Symbol: {s}"));
+        if !s.is_ascii() || s.len() < 16 {
+            return Err(invalid());
+        }
+
+        let (root, rest) = s.split_at(s.len() - 15);
+        let (date, rest) = rest.split_at(6);
+        let (option_type, strike) = rest.split_at(1);
+
+        let expiration = NaiveDate::parse_from_str(date, "%y%m%d").map_err(|_| invalid())?;
+        let option_type = match option_type {
+            "C" => OptionType::Call,
+            "P" => OptionType::Put,
+            _ => return Err(invalid()),
+        };
+        if !strike.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let strike = strike.parse().map_err(|_| invalid())?;
+
+        Self::from_parts(root.trim_end(), expiration, option_type, strike)
+    }
+}
+
+impl Serialize for OccSymbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.symbol)
+    }
+}
+
+impl<'de> Deserialize<'de> for OccSymbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occ_symbol_parse() {
+        let symbol: OccSymbol = "A240920C00105000".parse().unwrap();
+        assert_eq!(symbol.root(), "A");
+        assert_eq!(
+            symbol.expiration(),
+            NaiveDate::from_ymd_opt(2024, 9, 20).unwrap()
+        );
+        assert_eq!(symbol.option_type(), OptionType::Call);
+        assert_eq!(symbol.strike(), 105.0);
+        assert_eq!(symbol.padded(), "A     240920C00105000");
+
+        let padded: OccSymbol = "SPXW  241220P05912500".parse().unwrap();
+        assert_eq!(padded.to_string(), "SPXW241220P05912500");
+        assert_eq!(padded.strike(), 5912.5);
+    }
+
+    #[test]
+    fn test_occ_symbol_build() {
+        let expiration = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+        let symbol = OccSymbol::new("AAPL", expiration, OptionType::Call, 300.0).unwrap();
+        assert_eq!(symbol.as_str(), "AAPL241220C00300000");
+
+        let symbol = OccSymbol::new("AAPL", expiration, OptionType::Put, 17.125).unwrap();
+        assert_eq!(symbol.as_str(), "AAPL241220P00017125");
+
+        assert!(OccSymbol::new("AAPL", expiration, OptionType::Put, 1.0005).is_err());
+        assert!(OccSymbol::new("AAPL", expiration, OptionType::Put, -1.0).is_err());
+        assert!(OccSymbol::new("aapl", expiration, OptionType::Put, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_occ_symbol_invalid() {
+        assert!("AAPL".parse::<OccSymbol>().is_err());
+        assert!("AAPL241320C00300000".parse::<OccSymbol>().is_err());
+        assert!("AAPL241220X00300000".parse::<OccSymbol>().is_err());
+        assert!("AAPL241220C0030000A".parse::<OccSymbol>().is_err());
+        assert!("TOOLONGR241220C00300000".parse::<OccSymbol>().is_err());
+    }
+
+    #[test]
+    fn test_occ_symbol_serde() {
+        let symbol: OccSymbol = serde_json::from_str("\"AAPL241220C00300000\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&symbol).unwrap(),
+            "\"AAPL241220C00300000\""
+        );
+    }
+}
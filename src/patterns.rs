@@ -0,0 +1,438 @@
+use crate::{indicators::Candle, Trend};
+
+/// Candlestick and market structure patterns that can be detected
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PatternKind {
+    Doji,
+    Hammer,
+    ShootingStar,
+    BullishEngulfing,
+    BearishEngulfing,
+    MorningStar,
+    EveningStar,
+    ThreeWhiteSoldiers,
+    ThreeBlackCrows,
+    /// A swing high and swing low both above the previous ones
+    HigherHighs,
+    /// A swing high and swing low both below the previous ones
+    LowerLows,
+    /// A close above every high of the lookback window
+    Breakout,
+    /// A close below every low of the lookback window
+    Breakdown,
+}
+
+/// A detected pattern, `index` is the bar completing it
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatternHit {
+    pub kind: PatternKind,
+    pub index: usize,
+    /// `None` for neutral patterns such as the doji
+    pub trend: Option<Trend>,
+    /// Between 0 and 1, how cleanly the bars match the pattern
+    pub confidence: f64,
+}
+
+impl PatternHit {
+    fn new(kind: PatternKind, index: usize, trend: Option<Trend>, confidence: f64) -> Self {
+        Self {
+            kind,
+            index,
+            trend,
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Body and shadow measurements of a single candle
+struct Shape {
+    open: f64,
+    close: f64,
+    body: f64,
+    range: f64,
+    upper: f64,
+    lower: f64,
+}
+
+impl Shape {
+    fn of<C: Candle>(candle: &C) -> Self {
+        let (open, close) = (candle.open(), candle.close());
+        Self {
+            open,
+            close,
+            body: (close - open).abs(),
+            range: candle.high() - candle.low(),
+            upper: candle.high() - open.max(close),
+            lower: open.min(close) - candle.low(),
+        }
+    }
+
+    fn bullish(&self) -> bool {
+        self.close > self.open
+    }
+
+    fn bearish(&self) -> bool {
+        self.close < self.open
+    }
+
+    /// Body is at least half the range
+    fn long(&self) -> bool {
+        self.range > 0.0 && self.body >= self.range / 2.0
+    }
+
+    fn midpoint(&self) -> f64 {
+        (self.open + self.close) / 2.0
+    }
+}
+
+/// Detect candlestick patterns, hits are ordered by the bar completing them
+pub fn detect_candlestick_patterns<C: Candle>(candles: &[C]) -> Vec<PatternHit> {
+    let shapes: Vec<Shape> = candles.iter().map(Shape::of).collect();
+    let mut hits = Vec::new();
+
+    for (i, shape) in shapes.iter().enumerate() {
+        hits.extend(single_candle(shape, i));
+        if i >= 1 {
+            hits.extend(engulfing(&shapes[i - 1], shape, i));
+        }
+        if i >= 2 {
+            hits.extend(star(&shapes[i - 2], &shapes[i - 1], shape, i));
+            hits.extend(three_in_a_row(&shapes[i - 2..=i], i));
+        }
+    }
+
+    hits
+}
+
+fn single_candle(shape: &Shape, index: usize) -> Option<PatternHit> {
+    if shape.range <= 0.0 {
+        return None;
+    }
+
+    if shape.body <= shape.range * 0.1 {
+        let confidence = 1.0 - shape.body / (shape.range * 0.1);
+        return Some(PatternHit::new(PatternKind::Doji, index, None, confidence));
+    }
+
+    // A long shadow on one side only, at least twice the body
+    let confidence = |shadow: f64| shadow / shape.range;
+    if shape.lower >= shape.body * 2.0 && shape.upper <= shape.body * 0.5 {
+        return Some(PatternHit::new(
+            PatternKind::Hammer,
+            index,
+            Some(Trend::Bullish),
+            confidence(shape.lower),
+        ));
+    }
+    if shape.upper >= shape.body * 2.0 && shape.lower <= shape.body * 0.5 {
+        return Some(PatternHit::new(
+            PatternKind::ShootingStar,
+            index,
+            Some(Trend::Bearish),
+            confidence(shape.upper),
+        ));
+    }
+
+    None
+}
+
+fn engulfing(previous: &Shape, current: &Shape, index: usize) -> Option<PatternHit> {
+    if current.body <= previous.body {
+        return None;
+    }
+    let confidence = 0.5 + 0.5 * (1.0 - previous.body / current.body);
+
+    if previous.bearish()
+        && current.bullish()
+        && current.open <= previous.close
+        && current.close >= previous.open
+    {
+        Some(PatternHit::new(
+            PatternKind::BullishEngulfing,
+            index,
+            Some(Trend::Bullish),
+            confidence,
+        ))
+    } else if previous.bullish()
+        && current.bearish()
+        && current.open >= previous.close
+        && current.close <= previous.open
+    {
+        Some(PatternHit::new(
+            PatternKind::BearishEngulfing,
+            index,
+            Some(Trend::Bearish),
+            confidence,
+        ))
+    } else {
+        None
+    }
+}
+
+fn star(first: &Shape, middle: &Shape, last: &Shape, index: usize) -> Option<PatternHit> {
+    if !first.long() || middle.body > first.body * 0.3 {
+        return None;
+    }
+    // How far the last candle closes back into the first candle's body, past its midpoint
+    let penetration = |distance: f64| 0.5 + 0.5 * distance / (first.body / 2.0);
+
+    if first.bearish() && last.bullish() && last.close > first.midpoint() {
+        Some(PatternHit::new(
+            PatternKind::MorningStar,
+            index,
+            Some(Trend::Bullish),
+            penetration(last.close - first.midpoint()),
+        ))
+    } else if first.bullish() && last.bearish() && last.close < first.midpoint() {
+        Some(PatternHit::new(
+            PatternKind::EveningStar,
+            index,
+            Some(Trend::Bearish),
+            penetration(first.midpoint() - last.close),
+        ))
+    } else {
+        None
+    }
+}
+
+fn three_in_a_row(shapes: &[Shape], index: usize) -> Option<PatternHit> {
+    if !shapes.iter().all(Shape::long) {
+        return None;
+    }
+    let confidence = shapes.iter().map(|s| s.body / s.range).sum::<f64>() / 3.0;
+    // Each candle opens inside the previous body and closes beyond it
+    let pairs = || shapes.windows(2).map(|pair| (&pair[0], &pair[1]));
+
+    if shapes.iter().all(Shape::bullish)
+        && pairs().all(|(a, b)| b.open >= a.open && b.open <= a.close && b.close > a.close)
+    {
+        Some(PatternHit::new(
+            PatternKind::ThreeWhiteSoldiers,
+            index,
+            Some(Trend::Bullish),
+            confidence,
+        ))
+    } else if shapes.iter().all(Shape::bearish)
+        && pairs().all(|(a, b)| b.open <= a.open && b.open >= a.close && b.close < a.close)
+    {
+        Some(PatternHit::new(
+            PatternKind::ThreeBlackCrows,
+            index,
+            Some(Trend::Bearish),
+            confidence,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Detect market structure with swing points confirmed by `lookback` bars either side
+/// and breakouts beyond the previous `lookback` bars
+///
+/// Structure hits are reported at the bar confirming the swing, so they never use
+/// bars that come after `index`.
+pub fn detect_market_structure<C: Candle>(candles: &[C], lookback: usize) -> Vec<PatternHit> {
+    let lookback = lookback.max(1);
+    let highs: Vec<f64> = candles.iter().map(Candle::high).collect();
+    let lows: Vec<f64> = candles.iter().map(Candle::low).collect();
+    let mut hits = Vec::new();
+
+    let mut swing_highs: Vec<f64> = Vec::new();
+    let mut swing_lows: Vec<f64> = Vec::new();
+    let mut run: (Option<Trend>, usize) = (None, 0);
+
+    for (i, candle) in candles.iter().enumerate() {
+        if i >= lookback {
+            let window = i - lookback..i;
+            let high = window.clone().map(|j| highs[j]).fold(f64::MIN, f64::max);
+            let low = window.map(|j| lows[j]).fold(f64::MAX, f64::min);
+            let range = (high - low).max(f64::EPSILON);
+
+            if candle.close() > high {
+                let confidence = 0.5 + (candle.close() - high) / range;
+                hits.push(PatternHit::new(
+                    PatternKind::Breakout,
+                    i,
+                    Some(Trend::Bullish),
+                    confidence,
+                ));
+            } else if candle.close() < low {
+                let confidence = 0.5 + (low - candle.close()) / range;
+                hits.push(PatternHit::new(
+                    PatternKind::Breakdown,
+                    i,
+                    Some(Trend::Bearish),
+                    confidence,
+                ));
+            }
+        }
+
+        // The bar `lookback` back is now confirmed or rejected as a swing point
+        let Some(pivot) = i.checked_sub(lookback).filter(|pivot| *pivot >= lookback) else {
+            continue;
+        };
+        let around = (pivot - lookback..=i).filter(|j| *j != pivot);
+        let mut confirmed = false;
+        if around.clone().all(|j| highs[j] < highs[pivot]) {
+            swing_highs.push(highs[pivot]);
+            confirmed = true;
+        }
+        if around.clone().all(|j| lows[j] > lows[pivot]) {
+            swing_lows.push(lows[pivot]);
+            confirmed = true;
+        }
+        if !confirmed || swing_highs.len() < 2 || swing_lows.len() < 2 {
+            continue;
+        }
+
+        let last_two = |swings: &[f64]| (swings[swings.len() - 2], swings[swings.len() - 1]);
+        let (previous_high, high) = last_two(&swing_highs);
+        let (previous_low, low) = last_two(&swing_lows);
+        let (kind, trend) = if high > previous_high && low > previous_low {
+            (PatternKind::HigherHighs, Trend::Bullish)
+        } else if high < previous_high && low < previous_low {
+            (PatternKind::LowerLows, Trend::Bearish)
+        } else {
+            run = (None, 0);
+            continue;
+        };
+
+        // Confidence grows with each consecutive confirmation of the same structure
+        run = match run {
+            (Some(previous), count) if previous == trend => (Some(trend.clone()), count + 1),
+            _ => (Some(trend.clone()), 1),
+        };
+        let confidence = run.1 as f64 / (run.1 as f64 + 1.0);
+        hits.push(PatternHit::new(kind, i, Some(trend), confidence));
+    }
+
+    hits
+}
+
+/// Detect both candlestick patterns and market structure, ordered by bar
+pub fn detect_patterns<C: Candle>(candles: &[C], lookback: usize) -> Vec<PatternHit> {
+    let mut hits = detect_candlestick_patterns(candles);
+    hits.extend(detect_market_structure(candles, lookback));
+    hits.sort_by_key(|hit| hit.index);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_data::stocks::StockBar;
+
+    fn bar(o: f32, h: f32, l: f32, c: f32) -> StockBar {
+        StockBar {
+            t: "2024-07-01T13:30:00Z".to_string(),
+            o,
+            h,
+            l,
+            c,
+            v: 100.0,
+            n: 1,
+            vw: c,
+        }
+    }
+
+    fn kinds(hits: &[PatternHit]) -> Vec<PatternKind> {
+        hits.iter().map(|hit| hit.kind).collect()
+    }
+
+    #[test]
+    fn test_single_candle_patterns() {
+        let hits = detect_candlestick_patterns(&[
+            bar(10.0, 11.0, 9.0, 10.0),
+            bar(10.0, 10.1, 7.0, 10.5),
+            bar(10.5, 13.0, 10.0, 10.0),
+        ]);
+
+        assert_eq!(
+            kinds(&hits),
+            vec![
+                PatternKind::Doji,
+                PatternKind::Hammer,
+                PatternKind::ShootingStar
+            ]
+        );
+        assert_eq!(hits[0].trend, None);
+        assert_eq!(hits[0].confidence, 1.0);
+        assert_eq!(hits[1].trend, Some(Trend::Bullish));
+        assert_eq!(hits[2].index, 2);
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let hits =
+            detect_candlestick_patterns(&[bar(11.0, 11.2, 9.8, 10.0), bar(9.8, 12.2, 9.7, 12.0)]);
+
+        assert_eq!(kinds(&hits), vec![PatternKind::BullishEngulfing]);
+        assert_eq!(hits[0].index, 1);
+        assert!(hits[0].confidence > 0.5);
+    }
+
+    #[test]
+    fn test_stars() {
+        let morning = detect_candlestick_patterns(&[
+            bar(20.0, 20.5, 14.5, 15.0),
+            bar(14.0, 14.8, 13.5, 14.2),
+            bar(14.5, 19.5, 14.4, 19.0),
+        ]);
+        assert!(kinds(&morning).contains(&PatternKind::MorningStar));
+
+        let evening = detect_candlestick_patterns(&[
+            bar(15.0, 20.5, 14.5, 20.0),
+            bar(21.0, 21.5, 20.2, 20.8),
+            bar(20.5, 20.6, 15.5, 16.0),
+        ]);
+        let hit = evening
+            .iter()
+            .find(|hit| hit.kind == PatternKind::EveningStar)
+            .unwrap();
+        assert_eq!(hit.trend, Some(Trend::Bearish));
+        assert_eq!(hit.index, 2);
+    }
+
+    #[test]
+    fn test_three_in_a_row() {
+        let soldiers = detect_candlestick_patterns(&[
+            bar(10.0, 11.1, 9.9, 11.0),
+            bar(10.5, 12.1, 10.4, 12.0),
+            bar(11.5, 13.1, 11.4, 13.0),
+        ]);
+        assert!(kinds(&soldiers).contains(&PatternKind::ThreeWhiteSoldiers));
+
+        let crows = detect_candlestick_patterns(&[
+            bar(13.0, 13.1, 11.9, 12.0),
+            bar(12.5, 12.6, 10.9, 11.0),
+            bar(11.5, 11.6, 9.9, 10.0),
+        ]);
+        assert!(kinds(&crows).contains(&PatternKind::ThreeBlackCrows));
+    }
+
+    #[test]
+    fn test_market_structure() {
+        // Zig zag with rising swings, then a close above everything before it
+        let highs = [5.0, 7.0, 4.0, 8.0, 5.0, 9.0, 6.0, 12.0];
+        let candles: Vec<StockBar> = highs
+            .iter()
+            .map(|high| bar(high - 1.0, *high, high - 2.0, high - 0.5))
+            .collect();
+        let hits = detect_market_structure(&candles, 1);
+
+        let structure: Vec<&PatternHit> = hits
+            .iter()
+            .filter(|hit| hit.kind == PatternKind::HigherHighs)
+            .collect();
+        assert_eq!(structure.len(), 3);
+        assert_eq!(structure[0].index, 5);
+        assert_eq!(structure[0].trend, Some(Trend::Bullish));
+        assert!(structure[2].confidence > structure[0].confidence);
+
+        let breakout = hits
+            .iter()
+            .find(|hit| hit.kind == PatternKind::Breakout && hit.index == 7)
+            .unwrap();
+        assert_eq!(breakout.trend, Some(Trend::Bullish));
+    }
+}
@@ -0,0 +1,449 @@
+use crate::{
+    market_data::options::{pricing::cdf, Greeks, OptionSnapshot},
+    trading::{assets::OptionContract, positions::Position},
+    OccSymbol, OptionType,
+};
+use std::io::Error;
+
+/// Contracts per option unless the contract says otherwise
+const DEFAULT_MULTIPLIER: f64 = 100.0;
+
+/// What a leg holds
+#[derive(Debug, PartialEq, Clone)]
+pub enum LegInstrument {
+    Stock,
+    Option { symbol: OccSymbol, multiplier: f64 },
+}
+
+/// A long or short holding in a strategy, `qty` is negative for short legs
+#[derive(Debug, PartialEq, Clone)]
+pub struct PayoffLeg {
+    pub instrument: LegInstrument,
+    pub qty: f64,
+    /// Price paid or received per share or per contract, before the multiplier
+    pub entry_price: f64,
+    pub greeks: Option<Greeks>,
+    pub implied_volatility: Option<f64>,
+}
+
+impl PayoffLeg {
+    pub fn stock(qty: f64, entry_price: f64) -> Self {
+        Self {
+            instrument: LegInstrument::Stock,
+            qty,
+            entry_price,
+            greeks: None,
+            implied_volatility: None,
+        }
+    }
+
+    pub fn option(symbol: OccSymbol, qty: f64, entry_price: f64) -> Self {
+        Self {
+            instrument: LegInstrument::Option {
+                symbol,
+                multiplier: DEFAULT_MULTIPLIER,
+            },
+            qty,
+            entry_price,
+            greeks: None,
+            implied_volatility: None,
+        }
+    }
+
+    pub fn greeks(mut self, greeks: Greeks) -> Self {
+        self.greeks = Some(greeks);
+        self
+    }
+
+    pub fn implied_volatility(mut self, implied_volatility: f64) -> Self {
+        self.implied_volatility = Some(implied_volatility);
+        self
+    }
+
+    /// An option leg priced at the snapshot's quote midpoint, or its last trade
+    pub fn from_contract(
+        contract: &OptionContract,
+        snapshot: &OptionSnapshot,
+        qty: f64,
+    ) -> Result<Self, Error> {
+        let symbol = contract.occ_symbol()?;
+        let multiplier = contract.multiplier.parse().unwrap_or(DEFAULT_MULTIPLIER);
+        let quote = snapshot
+            .latest_quote
+            .as_ref()
+            .filter(|quote| quote.bp > 0.0 && quote.ap > 0.0)
+            .map(|quote| (quote.bp as f64 + quote.ap as f64) / 2.0);
+        let entry_price = quote
+            .or(snapshot.latest_trade.as_ref().map(|trade| trade.p))
+            .ok_or_else(|| Error::other(format!("No Price For {symbol}")))?;
+
+        Ok(Self {
+            instrument: LegInstrument::Option { symbol, multiplier },
+            qty,
+            entry_price,
+            greeks: snapshot.greeks,
+            implied_volatility: snapshot.implied_volatility,
+        })
+    }
+
+    /// A leg from an open position, option positions are recognised by their OCC symbol
+    pub fn from_position(position: &Position) -> Result<Self, Error> {
+        let number = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|_| Error::other(format!("Invalid Number: {value}")))
+        };
+        let mut qty = number(&position.qty)?;
+        if position.side == "short" && qty > 0.0 {
+            qty = -qty;
+        }
+        let entry_price = number(&position.avg_entry_price)?;
+
+        Ok(match position.asset_class.as_str() {
+            "us_option" => Self::option(position.symbol.parse()?, qty, entry_price),
+            _ => Self::stock(qty, entry_price),
+        })
+    }
+
+    fn multiplier(&self) -> f64 {
+        match &self.instrument {
+            LegInstrument::Stock => 1.0,
+            LegInstrument::Option { multiplier, .. } => *multiplier,
+        }
+    }
+
+    fn strike(&self) -> Option<f64> {
+        match &self.instrument {
+            LegInstrument::Stock => None,
+            LegInstrument::Option { symbol, .. } => Some(symbol.strike()),
+        }
+    }
+
+    /// Profit or loss if the underlying settles at `price`
+    pub fn payoff_at(&self, price: f64) -> f64 {
+        let value = match &self.instrument {
+            LegInstrument::Stock => price,
+            LegInstrument::Option { symbol, .. } => match symbol.option_type() {
+                OptionType::Call => (price - symbol.strike()).max(0.0),
+                OptionType::Put => (symbol.strike() - price).max(0.0),
+            },
+        };
+        self.qty * self.multiplier() * (value - self.entry_price)
+    }
+}
+
+/// The best or worst outcome of a strategy at expiry
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Extremum {
+    Finite(f64),
+    Unlimited,
+}
+
+/// A set of legs on one underlying analysed together
+///
+/// Payoffs are taken at expiration with every option worth its intrinsic value,
+/// so strategies mixing expirations such as calendars are only approximated.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Strategy {
+    pub legs: Vec<PayoffLeg>,
+}
+
+impl Strategy {
+    pub fn new(legs: Vec<PayoffLeg>) -> Self {
+        Self { legs }
+    }
+
+    pub fn leg(mut self, leg: PayoffLeg) -> Self {
+        self.legs.push(leg);
+        self
+    }
+
+    /// Strategy on one underlying from open positions, e.g. the result of
+    /// `PositionsQuery::get_all_open_positions`. Only shares of `underlying` and options
+    /// whose OCC root is `underlying` are taken.
+    pub fn from_positions(positions: &[Position], underlying: &str) -> Result<Self, Error> {
+        let mut legs = Vec::new();
+        for position in positions {
+            let leg = PayoffLeg::from_position(position)?;
+            let symbol = match &leg.instrument {
+                LegInstrument::Stock => position.symbol.as_str(),
+                LegInstrument::Option { symbol, .. } => symbol.root(),
+            };
+            if symbol == underlying {
+                legs.push(leg);
+            }
+        }
+        Ok(Self { legs })
+    }
+
+    /// Profit or loss if the underlying settles at `price`
+    pub fn payoff_at(&self, price: f64) -> f64 {
+        self.legs.iter().map(|leg| leg.payoff_at(price)).sum()
+    }
+
+    /// `points` evenly spaced (price, profit or loss) pairs from `low` to `high`
+    pub fn payoff_curve(&self, low: f64, high: f64, points: usize) -> Vec<(f64, f64)> {
+        let points = points.max(2);
+        let step = (high - low) / (points - 1) as f64;
+        (0..points)
+            .map(|i| {
+                let price = low + step * i as f64;
+                (price, self.payoff_at(price))
+            })
+            .collect()
+    }
+
+    /// Zero and every strike in ascending order, the payoff is linear between them
+    fn kinks(&self) -> Vec<f64> {
+        let mut kinks: Vec<f64> = self.legs.iter().filter_map(PayoffLeg::strike).collect();
+        kinks.push(0.0);
+        kinks.sort_by(f64::total_cmp);
+        kinks.dedup();
+        kinks
+    }
+
+    /// Change in profit per dollar the underlying rises past the highest strike
+    fn upside_slope(&self) -> f64 {
+        let last = *self.kinks().last().unwrap_or(&0.0);
+        self.payoff_at(last + 1.0) - self.payoff_at(last)
+    }
+
+    /// Underlying prices at expiry where the strategy neither makes nor loses money
+    pub fn breakevens(&self) -> Vec<f64> {
+        let kinks = self.kinks();
+        let mut breakevens: Vec<f64> = Vec::new();
+        let mut push = |price: f64| {
+            if breakevens
+                .last()
+                .is_none_or(|last| (price - last).abs() > 1e-9)
+            {
+                breakevens.push(price);
+            }
+        };
+
+        for pair in kinks.windows(2) {
+            let (low, high) = (pair[0], pair[1]);
+            let (at_low, at_high) = (self.payoff_at(low), self.payoff_at(high));
+            if at_low == 0.0 {
+                push(low);
+            } else if at_low.signum() != at_high.signum() && at_high != 0.0 {
+                push(low + (high - low) * at_low / (at_low - at_high));
+            }
+        }
+
+        let last = *kinks.last().unwrap_or(&0.0);
+        let at_last = self.payoff_at(last);
+        let slope = self.upside_slope();
+        if at_last == 0.0 {
+            push(last);
+        } else if at_last * slope < 0.0 {
+            push(last - at_last / slope);
+        }
+
+        breakevens
+    }
+
+    fn extreme_payoffs(&self) -> impl Iterator<Item = f64> + '_ {
+        self.kinks().into_iter().map(|price| self.payoff_at(price))
+    }
+
+    pub fn max_profit(&self) -> Extremum {
+        if self.upside_slope() > 1e-9 {
+            return Extremum::Unlimited;
+        }
+        Extremum::Finite(self.extreme_payoffs().fold(f64::MIN, f64::max))
+    }
+
+    /// The worst outcome, negative when the strategy can lose money
+    pub fn max_loss(&self) -> Extremum {
+        if self.upside_slope() < -1e-9 {
+            return Extremum::Unlimited;
+        }
+        Extremum::Finite(self.extreme_payoffs().fold(f64::MAX, f64::min))
+    }
+
+    /// Chance the strategy ends in profit, with the underlying lognormal at the given
+    /// annualised volatility, years to expiry and risk free rate
+    pub fn probability_of_profit(&self, spot: f64, volatility: f64, years: f64, rate: f64) -> f64 {
+        let deviation = volatility * years.sqrt();
+        let below = |price: f64| {
+            if price <= 0.0 {
+                0.0
+            } else if deviation <= 0.0 {
+                if spot < price {
+                    1.0
+                } else {
+                    0.0
+                }
+            } else {
+                let drift = (rate - volatility * volatility / 2.0) * years;
+                cdf(((price / spot).ln() - drift) / deviation)
+            }
+        };
+
+        let mut bounds = vec![0.0];
+        bounds.extend(self.breakevens());
+        let mut probability = 0.0;
+        for (i, low) in bounds.iter().enumerate() {
+            let high = bounds.get(i + 1).copied();
+            let probe = match high {
+                Some(high) => (low + high) / 2.0,
+                None => low + 1.0,
+            };
+            if self.payoff_at(probe) > 0.0 {
+                probability += high.map_or(1.0, below) - below(*low);
+            }
+        }
+        probability
+    }
+
+    /// Mean implied volatility of the option legs that carry one
+    pub fn implied_volatility(&self) -> Option<f64> {
+        let volatilities: Vec<f64> = self
+            .legs
+            .iter()
+            .filter_map(|leg| leg.implied_volatility)
+            .collect();
+        (!volatilities.is_empty())
+            .then(|| volatilities.iter().sum::<f64>() / volatilities.len() as f64)
+    }
+
+    /// Position Greeks in share terms, each leg's Greeks scaled by quantity and multiplier
+    ///
+    /// Stock legs add their quantity to delta. Returns `None` if an option leg has no Greeks.
+    pub fn greeks(&self) -> Option<Greeks> {
+        let mut total = Greeks::default();
+        for leg in &self.legs {
+            let greeks = match leg.instrument {
+                LegInstrument::Stock => Greeks {
+                    delta: 1.0,
+                    ..Greeks::default()
+                },
+                LegInstrument::Option { .. } => leg.greeks?,
+            };
+            let scale = leg.qty * leg.multiplier();
+            total.delta += greeks.delta * scale;
+            total.gamma += greeks.gamma * scale;
+            total.rho += greeks.rho * scale;
+            total.theta += greeks.theta * scale;
+            total.vega += greeks.vega * scale;
+        }
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(symbol: &str, qty: f64, entry_price: f64) -> PayoffLeg {
+        PayoffLeg::option(symbol.parse().unwrap(), qty, entry_price)
+    }
+
+    fn close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-6, "{value} is not {expected}");
+    }
+
+    #[test]
+    fn test_bull_call_spread() {
+        let spread = Strategy::new(vec![
+            option("AAPL241220C00100000", 1.0, 5.0),
+            option("AAPL241220C00110000", -1.0, 2.0),
+        ]);
+
+        close(spread.payoff_at(90.0), -300.0);
+        close(spread.payoff_at(120.0), 700.0);
+        assert_eq!(spread.breakevens(), vec![103.0]);
+        assert_eq!(spread.max_profit(), Extremum::Finite(700.0));
+        assert_eq!(spread.max_loss(), Extremum::Finite(-300.0));
+
+        let curve = spread.payoff_curve(100.0, 110.0, 3);
+        assert_eq!(curve, vec![(100.0, -300.0), (105.0, 200.0), (110.0, 700.0)]);
+    }
+
+    #[test]
+    fn test_unlimited_outcomes() {
+        let straddle = Strategy::new(vec![
+            option("AAPL241220C00100000", 1.0, 4.0),
+            option("AAPL241220P00100000", 1.0, 3.0),
+        ]);
+        assert_eq!(straddle.breakevens(), vec![93.0, 107.0]);
+        assert_eq!(straddle.max_profit(), Extremum::Unlimited);
+        assert_eq!(straddle.max_loss(), Extremum::Finite(-700.0));
+
+        let covered_call = Strategy::new(vec![
+            PayoffLeg::stock(100.0, 95.0),
+            option("AAPL241220C00100000", -1.0, 2.0),
+        ]);
+        assert_eq!(covered_call.breakevens(), vec![93.0]);
+        assert_eq!(covered_call.max_profit(), Extremum::Finite(700.0));
+
+        let naked_call = Strategy::new(vec![option("AAPL241220C00100000", -1.0, 2.0)]);
+        assert_eq!(naked_call.max_loss(), Extremum::Unlimited);
+        assert_eq!(naked_call.breakevens(), vec![102.0]);
+    }
+
+    #[test]
+    fn test_probability_of_profit() {
+        let long_stock = Strategy::new(vec![PayoffLeg::stock(1.0, 100.0)]);
+        // With no drift the median is below spot, so profit is a little under even odds
+        let probability = long_stock.probability_of_profit(100.0, 0.2, 1.0, 0.0);
+        close(probability, 1.0 - cdf(0.1));
+
+        let spread = Strategy::new(vec![
+            option("AAPL241220C00100000", 1.0, 5.0),
+            option("AAPL241220C00110000", -1.0, 2.0),
+        ]);
+        let probability = spread.probability_of_profit(102.5, 0.0, 1.0, 0.0);
+        assert_eq!(probability, 0.0);
+        let probability = spread.probability_of_profit(104.0, 0.0, 1.0, 0.0);
+        assert_eq!(probability, 1.0);
+    }
+
+    #[test]
+    fn test_position_greeks() {
+        let greeks = Greeks {
+            delta: 0.5,
+            gamma: 0.02,
+            rho: 0.1,
+            theta: -0.05,
+            vega: 0.2,
+        };
+        let strategy = Strategy::new(vec![
+            PayoffLeg::stock(-50.0, 100.0),
+            option("AAPL241220C00100000", 2.0, 4.0).greeks(greeks),
+        ]);
+
+        let total = strategy.greeks().unwrap();
+        close(total.delta, 50.0);
+        close(total.gamma, 4.0);
+        close(total.theta, -10.0);
+
+        let missing = strategy.leg(option("AAPL241220P00100000", 1.0, 3.0));
+        assert!(missing.greeks().is_none());
+    }
+
+    #[test]
+    fn test_legs_from_positions() {
+        let positions: Vec<Position> = serde_json::from_str(
+            r#"[
+                {"asset_id":"1","symbol":"AAPL","exchange":"NASDAQ","asset_class":"us_equity","avg_entry_price":"95","qty":"100","qty_available":"100","side":"long","market_value":"10000","cost_basis":"9500","unrealized_pl":"500","unrealized_plpc":"0.05","unrealized_intraday_pl":"0","unrealized_intraday_plpc":"0","current_price":"100","lastday_price":"100","change_today":"0"},
+                {"asset_id":"2","symbol":"AAPL241220C00100000","exchange":"","asset_class":"us_option","avg_entry_price":"2","qty":"-1","qty_available":"-1","side":"short","market_value":"-300","cost_basis":"-200","unrealized_pl":"-100","unrealized_plpc":"-0.5","unrealized_intraday_pl":"0","unrealized_intraday_plpc":"0","current_price":"3","lastday_price":"3","change_today":"0"},
+                {"asset_id":"3","symbol":"MSFT","exchange":"NASDAQ","asset_class":"us_equity","avg_entry_price":"400","qty":"10","qty_available":"10","side":"long","market_value":"4100","cost_basis":"4000","unrealized_pl":"100","unrealized_plpc":"0.025","unrealized_intraday_pl":"0","unrealized_intraday_plpc":"0","current_price":"410","lastday_price":"410","change_today":"0"},
+                {"asset_id":"4","symbol":"MSFT241220P00400000","exchange":"","asset_class":"us_option","avg_entry_price":"5","qty":"2","qty_available":"2","side":"long","market_value":"1000","cost_basis":"1000","unrealized_pl":"0","unrealized_plpc":"0","unrealized_intraday_pl":"0","unrealized_intraday_plpc":"0","current_price":"5","lastday_price":"5","change_today":"0"}
+            ]"#,
+        )
+        .unwrap();
+
+        let strategy = Strategy::from_positions(&positions, "AAPL").unwrap();
+        assert_eq!(strategy.legs.len(), 2);
+        assert_eq!(strategy.legs[0].instrument, LegInstrument::Stock);
+        assert_eq!(strategy.legs[1].qty, -1.0);
+        assert_eq!(strategy.breakevens(), vec![93.0]);
+
+        let strategy = Strategy::from_positions(&positions, "MSFT").unwrap();
+        assert_eq!(strategy.legs.len(), 2);
+        assert_eq!(strategy.legs[0].entry_price, 400.0);
+        assert_eq!(strategy.legs[1].strike(), Some(400.0));
+    }
+}
@@ -1,8 +1,9 @@
-use serde::Deserialize;
+use crate::trading::order::Order;
+use serde::{Deserialize, Serialize};
 
 // * This is the object that comes from the alpaca websocket for bars
 #[allow(dead_code)]
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StreamBar {
     #[serde(rename = "T")]
     pub bar_type: String,
@@ -17,3 +18,15 @@ pub struct StreamBar {
     pub n: u32,
     pub vw: f32,
 }
+
+// * This is the object that comes from the alpaca websocket for trade updates
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TradeUpdate {
+    pub event: String,
+    pub order: Order,
+    pub timestamp: Option<String>,
+    pub price: Option<String>,
+    pub qty: Option<String>,
+    pub position_qty: Option<String>,
+    pub execution_id: Option<String>,
+}
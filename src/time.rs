@@ -0,0 +1,81 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use std::io::{Error, ErrorKind};
+
+/// Parse an RFC-3339 timestamp as returned by the Alpaca API
+pub(crate) fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, Error> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid Timestamp: {timestamp}"),
+            )
+        })
+}
+
+/// Format a timestamp the way Alpaca does, e.g. 2024-01-02T14:30:00Z
+pub(crate) fn format_timestamp(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()
+}
+
+pub(crate) fn parse_date(date: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| Error::new(ErrorKind::InvalidData, format!("Invalid Date: {date}")))
+}
+
+/// UTC offset of New York on a given date, daylight saving runs from the second
+/// Sunday of March to the first Sunday of November
+pub(crate) fn new_york_offset(date: NaiveDate) -> FixedOffset {
+    let nth_sunday = |month: u32, n: u8| {
+        NaiveDate::from_weekday_of_month_opt(date.year(), month, Weekday::Sun, n)
+            .expect("Every month has a first and second Sunday")
+    };
+    let daylight_saving = date >= nth_sunday(3, 2) && date < nth_sunday(11, 1);
+    let hours = if daylight_saving { -4 } else { -5 };
+    FixedOffset::east_opt(hours * 3600).expect("Offset is within a day")
+}
+
+/// Convert a New York wall clock time such as the calendar's "09:30" to UTC
+pub(crate) fn new_york_to_utc(date: NaiveDate, time: &str) -> Result<DateTime<Utc>, Error> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| Error::new(ErrorKind::InvalidData, format!("Invalid Time: {time}")))?;
+    new_york_offset(date)
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Ambiguous Local Time"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let datetime = parse_timestamp("2024-09-20T14:28:58.123Z").unwrap();
+        assert_eq!(format_timestamp(datetime), "2024-09-20T14:28:58.123Z");
+
+        let datetime = parse_timestamp("2022-02-01T05:00:00Z").unwrap();
+        assert_eq!(format_timestamp(datetime), "2022-02-01T05:00:00Z");
+    }
+
+    #[test]
+    fn test_new_york_offset() {
+        let winter = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let summer = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let dst_start = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let dst_end = NaiveDate::from_ymd_opt(2024, 11, 3).unwrap();
+
+        assert_eq!(new_york_offset(winter).local_minus_utc(), -5 * 3600);
+        assert_eq!(new_york_offset(summer).local_minus_utc(), -4 * 3600);
+        assert_eq!(new_york_offset(dst_start).local_minus_utc(), -4 * 3600);
+        assert_eq!(new_york_offset(dst_end).local_minus_utc(), -5 * 3600);
+    }
+
+    #[test]
+    fn test_new_york_to_utc() {
+        let date = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let open = new_york_to_utc(date, "09:30").unwrap();
+        assert_eq!(format_timestamp(open), "2024-07-01T13:30:00Z");
+    }
+}
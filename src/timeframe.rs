@@ -1,59 +1,201 @@
-use std::{io::Error, str::FromStr};
-
-/// Timeframe options utility for time frames accepted by alpaca api
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub enum TimeFrame {
-    OneMinute,
-    FiveMinutes,
-    FifteenMinutes,
-    ThirtyMinutes,
-    OneHour,
-    FourHours,
-    OneDay,
-    OneWeek,
-    OneMonth,
-}
-
-impl ToString for TimeFrame {
-    fn to_string(&self) -> String {
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, io::Error, str::FromStr, time::Duration};
+
+/// Unit of a [`TimeFrame`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TimeFrameUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeFrameUnit {
+    fn name(&self) -> &'static str {
+        match self {
+            TimeFrameUnit::Minute => "Min",
+            TimeFrameUnit::Hour => "Hour",
+            TimeFrameUnit::Day => "Day",
+            TimeFrameUnit::Week => "Week",
+            TimeFrameUnit::Month => "Month",
+        }
+    }
+
+    fn is_valid(&self, amount: u32) -> bool {
         match self {
-            TimeFrame::OneMinute => "1Min".to_string(),
-            TimeFrame::FiveMinutes => "5Min".to_string(),
-            TimeFrame::FifteenMinutes => "15Min".to_string(),
-            TimeFrame::ThirtyMinutes => "30Min".to_string(),
-            TimeFrame::OneHour => "1H".to_string(),
-            TimeFrame::FourHours => "4H".to_string(),
-            TimeFrame::OneDay => "1D".to_string(),
-            TimeFrame::OneWeek => "1W".to_string(),
-            TimeFrame::OneMonth => "1M".to_string(),
+            TimeFrameUnit::Minute => (1..=59).contains(&amount),
+            TimeFrameUnit::Hour => (1..=23).contains(&amount),
+            TimeFrameUnit::Day | TimeFrameUnit::Week => amount == 1,
+            TimeFrameUnit::Month => [1, 2, 3, 4, 6, 12].contains(&amount),
+        }
+    }
+}
+
+/// Timeframe utility for any amount and unit accepted by alpaca api
+///
+/// Valid values are `[1-59]Min`, `[1-23]Hour`, `1Day`, `1Week` and `[1,2,3,4,6,12]Month`.
+/// Parsing also accepts the short units `T`, `H`, `D`, `W` and `M`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct TimeFrame {
+    amount: u32,
+    unit: TimeFrameUnit,
+}
+
+#[allow(non_upper_case_globals)]
+impl TimeFrame {
+    pub const OneMinute: TimeFrame = TimeFrame::unchecked(1, TimeFrameUnit::Minute);
+    pub const FiveMinutes: TimeFrame = TimeFrame::unchecked(5, TimeFrameUnit::Minute);
+    pub const FifteenMinutes: TimeFrame = TimeFrame::unchecked(15, TimeFrameUnit::Minute);
+    pub const ThirtyMinutes: TimeFrame = TimeFrame::unchecked(30, TimeFrameUnit::Minute);
+    pub const OneHour: TimeFrame = TimeFrame::unchecked(1, TimeFrameUnit::Hour);
+    pub const FourHours: TimeFrame = TimeFrame::unchecked(4, TimeFrameUnit::Hour);
+    pub const OneDay: TimeFrame = TimeFrame::unchecked(1, TimeFrameUnit::Day);
+    pub const OneWeek: TimeFrame = TimeFrame::unchecked(1, TimeFrameUnit::Week);
+    pub const OneMonth: TimeFrame = TimeFrame::unchecked(1, TimeFrameUnit::Month);
+}
+
+impl TimeFrame {
+    const fn unchecked(amount: u32, unit: TimeFrameUnit) -> Self {
+        Self { amount, unit }
+    }
+
+    /// Create a timeframe, erroring if alpaca does not accept the amount for the unit
+    pub fn new(amount: u32, unit: TimeFrameUnit) -> Result<Self, Error> {
+        if unit.is_valid(amount) {
+            Ok(Self { amount, unit })
+        } else {
+            Err(Error::other(format!(
+                "Invalid Timeframe: {amount}{}",
+                unit.name()
+            )))
+        }
+    }
+
+    pub fn minutes(amount: u32) -> Result<Self, Error> {
+        Self::new(amount, TimeFrameUnit::Minute)
+    }
+
+    pub fn hours(amount: u32) -> Result<Self, Error> {
+        Self::new(amount, TimeFrameUnit::Hour)
+    }
+
+    pub fn months(amount: u32) -> Result<Self, Error> {
+        Self::new(amount, TimeFrameUnit::Month)
+    }
+
+    pub fn amount(&self) -> u32 {
+        self.amount
+    }
+
+    pub fn unit(&self) -> TimeFrameUnit {
+        self.unit
+    }
+
+    /// Length of one bar, months have no fixed length so return `None`
+    pub fn duration(&self) -> Option<Duration> {
+        let seconds = match self.unit {
+            TimeFrameUnit::Minute => 60,
+            TimeFrameUnit::Hour => 3600,
+            TimeFrameUnit::Day => 86400,
+            TimeFrameUnit::Week => 604800,
+            TimeFrameUnit::Month => return None,
+        };
+        Some(Duration::from_secs(seconds * self.amount as u64))
+    }
+
+    /// Short form used by the portfolio history endpoint, e.g. 15Min, 1H or 1D
+    pub(crate) fn short_form(&self) -> String {
+        match self.unit {
+            TimeFrameUnit::Minute => format!("{}Min", self.amount),
+            TimeFrameUnit::Hour => format!("{}H", self.amount),
+            TimeFrameUnit::Day => format!("{}D", self.amount),
+            TimeFrameUnit::Week => format!("{}W", self.amount),
+            TimeFrameUnit::Month => format!("{}M", self.amount),
         }
     }
 }
 
+impl fmt::Display for TimeFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit.name())
+    }
+}
+
 impl FromStr for TimeFrame {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "1Min" => Ok(TimeFrame::OneMinute),
-            "5Min" => Ok(TimeFrame::FiveMinutes),
-            "15Min" => Ok(TimeFrame::FifteenMinutes),
-            "30Min" => Ok(TimeFrame::ThirtyMinutes),
-            "1Hour" => Ok(TimeFrame::OneHour),
-            "4Hour" => Ok(TimeFrame::FourHours),
-            "1Day" => Ok(TimeFrame::OneDay),
-            "1Week" => Ok(TimeFrame::OneWeek),
-            "1Month" => Ok(TimeFrame::OneMonth),
-            "1T" => Ok(TimeFrame::OneMinute),
-            "5T" => Ok(TimeFrame::FiveMinutes),
-            "15T" => Ok(TimeFrame::FifteenMinutes),
-            "30T" => Ok(TimeFrame::ThirtyMinutes),
-            "1H" => Ok(TimeFrame::OneHour),
-            "4H" => Ok(TimeFrame::FourHours),
-            "1D" => Ok(TimeFrame::OneDay),
-            "1W" => Ok(TimeFrame::OneWeek),
-            "1M" => Ok(TimeFrame::OneMonth),
-            _ => Err(Error::other("Timeframes Do Not Match")),
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let amount = amount
+            .parse()
+            .map_err(|_| Error::other("Timeframes Do Not Match"))?;
+
+        let unit = match unit {
+            "Min" | "T" => TimeFrameUnit::Minute,
+            "Hour" | "H" => TimeFrameUnit::Hour,
+            "Day" | "D" => TimeFrameUnit::Day,
+            "Week" | "W" => TimeFrameUnit::Week,
+            "Month" | "M" => TimeFrameUnit::Month,
+            _ => return Err(Error::other("Timeframes Do Not Match")),
+        };
+
+        Self::new(amount, unit)
+    }
+}
+
+impl Serialize for TimeFrame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeFrame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeframe_round_trip() {
+        for s in ["1Min", "45Min", "23Hour", "1Day", "1Week", "6Month"] {
+            assert_eq!(s.parse::<TimeFrame>().unwrap().to_string(), s);
         }
+        assert_eq!("1H".parse::<TimeFrame>().unwrap(), TimeFrame::OneHour);
+        assert_eq!(
+            "15T".parse::<TimeFrame>().unwrap(),
+            TimeFrame::FifteenMinutes
+        );
+        assert_eq!(TimeFrame::OneHour.short_form(), "1H");
+    }
+
+    #[test]
+    fn test_timeframe_validation() {
+        assert!("60Min".parse::<TimeFrame>().is_err());
+        assert!("24Hour".parse::<TimeFrame>().is_err());
+        assert!("2Day".parse::<TimeFrame>().is_err());
+        assert!("5Month".parse::<TimeFrame>().is_err());
+        assert!("0Min".parse::<TimeFrame>().is_err());
+        assert!("1Sec".parse::<TimeFrame>().is_err());
+    }
+
+    #[test]
+    fn test_timeframe_serde_and_duration() {
+        let json = serde_json::to_string(&TimeFrame::FourHours).unwrap();
+        assert_eq!(json, "\"4Hour\"");
+        let timeframe: TimeFrame = serde_json::from_str(&json).unwrap();
+        assert_eq!(timeframe, TimeFrame::FourHours);
+
+        assert_eq!(
+            TimeFrame::FiveMinutes.duration(),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(TimeFrame::OneMonth.duration(), None);
     }
 }
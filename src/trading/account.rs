@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use super::AccountType;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct AccountConfiguration {
     pub dtbp_check: Option<String>,
     pub trade_confirm_email: Option<String>,
@@ -17,7 +17,7 @@ pub struct AccountConfiguration {
     pub ptp_no_exception_entry: Option<bool>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Account {
     pub id: String,
     pub admin_configurations: HashMap<String, String>,
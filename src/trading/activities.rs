@@ -1,8 +1,8 @@
 use super::AccountType;
-use crate::request;
-use serde::Deserialize;
+use crate::{request, OccSymbol};
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct TradeActivity {
     pub activity_type: Option<String>,
     pub id: String,
@@ -21,10 +21,77 @@ pub struct TradeActivity {
     pub per_share_amount: Option<String>,
     pub group_id: Option<String>,
     pub status: Option<String>,
+    pub description: Option<String>,
 }
 
 pub type TradeActivities = Vec<TradeActivity>;
 
+/// Non-trade activity types for the end of an option contract's life
+pub const OPTION_LIFECYCLE_ACTIVITY_TYPES: [&str; 3] = ["OPEXC", "OPASN", "OPEXP"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionEventKind {
+    /// OPEXC, a held contract was exercised
+    Exercise,
+    /// OPASN, a written contract was assigned
+    Assignment,
+    /// OPEXP, a contract expired worthless
+    Expiration,
+}
+
+impl OptionEventKind {
+    pub fn from_activity_type(activity_type: &str) -> Option<Self> {
+        match activity_type {
+            "OPEXC" => Some(OptionEventKind::Exercise),
+            "OPASN" => Some(OptionEventKind::Assignment),
+            "OPEXP" => Some(OptionEventKind::Expiration),
+            _ => None,
+        }
+    }
+}
+
+/// An option exercise, assignment or expiration read from a [`TradeActivity`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionEvent {
+    pub kind: OptionEventKind,
+    pub id: String,
+    /// `None` when the activity symbol is not an OCC symbol
+    pub contract: Option<OccSymbol>,
+    pub qty: Option<f64>,
+    pub net_amount: Option<f64>,
+    pub date: Option<String>,
+    pub description: Option<String>,
+}
+
+impl TradeActivity {
+    /// The typed option lifecycle event, if this is an OPEXC, OPASN or OPEXP activity
+    pub fn option_event(&self) -> Option<OptionEvent> {
+        let kind = OptionEventKind::from_activity_type(self.activity_type.as_deref()?)?;
+        let number = |value: &Option<String>| value.as_deref().and_then(|v| v.parse().ok());
+
+        Some(OptionEvent {
+            kind,
+            id: self.id.clone(),
+            contract: self
+                .symbol
+                .as_deref()
+                .and_then(|symbol| symbol.parse().ok()),
+            qty: number(&self.qty),
+            net_amount: number(&self.net_amount),
+            date: self.date.clone(),
+            description: self.description.clone(),
+        })
+    }
+}
+
+/// Every option lifecycle event among the activities, in their original order
+pub fn option_events(activities: &[TradeActivity]) -> Vec<OptionEvent> {
+    activities
+        .iter()
+        .filter_map(TradeActivity::option_event)
+        .collect()
+}
+
 pub struct ActivitiesQuery<'a> {
     url: &'a str,
     activity_types: Option<Vec<&'a str>>,
@@ -61,6 +128,11 @@ impl<'a> ActivitiesQuery<'a> {
         self
     }
 
+    /// Only request option exercise, assignment and expiration activities
+    pub fn option_lifecycle(self) -> Self {
+        self.activity_types(OPTION_LIFECYCLE_ACTIVITY_TYPES.to_vec())
+    }
+
     pub fn category(mut self, category: &'a str) -> Self {
         self.category = Some(category);
         self.activity_types = None;
@@ -184,4 +256,27 @@ mod tests {
         dbg!(&query);
         assert!(query.len() > 0);
     }
+
+    #[test]
+    fn test_option_events() {
+        let activities: TradeActivities = serde_json::from_str(
+            r#"[
+                {"activity_type":"OPASN","id":"20240920000000000::1","date":"2024-09-20","net_amount":"0","description":"Option Assignment","symbol":"AAPL240920C00220000","qty":"-1","status":"executed"},
+                {"activity_type":"FILL","id":"20240919000000000::2","symbol":"AAPL","qty":"1","price":"220"},
+                {"activity_type":"OPEXP","id":"20240920000000000::3","date":"2024-09-20","symbol":"AAPL240920P00200000","qty":"2"}
+            ]"#,
+        )
+        .unwrap();
+
+        let events = option_events(&activities);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, OptionEventKind::Assignment);
+        assert_eq!(events[0].qty, Some(-1.0));
+        assert_eq!(events[0].contract.as_ref().unwrap().strike(), 220.0);
+        assert_eq!(events[1].kind, OptionEventKind::Expiration);
+        assert!(activities[1].option_event().is_none());
+
+        let query = ActivitiesQuery::new(AccountType::Paper).option_lifecycle();
+        assert!(query.build().contains("activity_types=OPEXC,OPASN,OPEXP"));
+    }
 }
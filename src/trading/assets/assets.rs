@@ -1,7 +1,7 @@
 use crate::{request, trading::AccountType};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Asset {
     pub id: String,
     pub class: String,
@@ -21,6 +21,28 @@ pub struct Asset {
 
 pub type AllAssets = Vec<Asset>;
 
+/// An active, tradable AAPL asset for tests, with any fields in `overrides` replaced
+#[cfg(test)]
+pub(crate) fn test_asset(overrides: serde_json::Value) -> Asset {
+    let mut asset = serde_json::json!({
+        "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "class": "us_equity",
+        "exchange": "NASDAQ",
+        "symbol": "AAPL",
+        "name": "Apple Inc. Common Stock",
+        "status": "active",
+        "tradable": true,
+        "marginable": true,
+        "shortable": true,
+        "easy_to_borrow": true,
+        "fractionable": true
+    });
+    if let (Some(asset), Some(overrides)) = (asset.as_object_mut(), overrides.as_object()) {
+        asset.extend(overrides.clone());
+    }
+    serde_json::from_value(asset).expect("Test asset is valid")
+}
+
 pub struct AssetsQuery<'a> {
     pub url: &'a str,
     pub status: Option<&'a str>,
@@ -1,8 +1,9 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::io::Error;
 
-use crate::{request, trading::AccountType};
+use crate::{request, trading::AccountType, OccSymbol, OptionType};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OptionContract {
     pub id: String,
     pub symbol: String,
@@ -25,7 +26,18 @@ pub struct OptionContract {
     pub deliverables: Option<Vec<Deliverable>>,
 }
 
-#[derive(Deserialize, Debug)]
+impl OptionContract {
+    /// The contract symbol parsed into its root, expiration, type and strike
+    pub fn occ_symbol(&self) -> Result<OccSymbol, Error> {
+        self.symbol.parse()
+    }
+
+    pub fn option_type(&self) -> Result<OptionType, Error> {
+        self.r#type.parse()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Deliverable {
     pub r#type: String,
     pub symbol: String,
@@ -39,7 +51,7 @@ pub struct Deliverable {
 
 pub type AllOptionContracts = Vec<OptionContract>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct OptionContractsResponse {
     pub option_contracts: AllOptionContracts,
     pub next_page_token: Option<String>,
@@ -245,8 +257,11 @@ impl<'a> OptionContractsQuery<'a> {
         Ok(asset)
     }
 
-    pub fn get_by_symbol(self, symbol: &'a str) -> Result<OptionContract, ureq::Error> {
-        let route = format!("{}/{}", self.url, symbol);
+    pub fn get_by_symbol<S: AsRef<str> + ?Sized>(
+        self,
+        symbol: &S,
+    ) -> Result<OptionContract, ureq::Error> {
+        let route = format!("{}/{}", self.url, symbol.as_ref());
         let response = request("GET", &route).call()?;
         let asset: OptionContract = response.into_json()?;
         Ok(asset)
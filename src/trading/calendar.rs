@@ -1,8 +1,13 @@
 use super::AccountType;
-use crate::request;
-use serde::Deserialize;
+use crate::{
+    request,
+    time::{new_york_to_utc, parse_date},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Error;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CalendarDay {
     pub date: String,
     pub open: String,
@@ -10,6 +15,18 @@ pub struct CalendarDay {
     pub settlement_date: String,
 }
 
+impl CalendarDay {
+    /// Market open as a UTC timestamp, the calendar itself is in New York time
+    pub fn open_at(&self) -> Result<DateTime<Utc>, Error> {
+        new_york_to_utc(parse_date(&self.date)?, &self.open)
+    }
+
+    /// Market close as a UTC timestamp, the calendar itself is in New York time
+    pub fn close_at(&self) -> Result<DateTime<Utc>, Error> {
+        new_york_to_utc(parse_date(&self.date)?, &self.close)
+    }
+}
+
 pub type Calendar = Vec<CalendarDay>;
 
 pub struct CalendarQuery<'a> {
@@ -73,6 +90,25 @@ impl<'a> CalendarQuery<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_calendar_day_session_times() {
+        let day = CalendarDay {
+            date: "2024-11-29".to_string(),
+            open: "09:30".to_string(),
+            close: "13:00".to_string(),
+            settlement_date: "2024-12-02".to_string(),
+        };
+
+        assert_eq!(
+            day.open_at().unwrap().to_rfc3339(),
+            "2024-11-29T14:30:00+00:00"
+        );
+        assert_eq!(
+            day.close_at().unwrap().to_rfc3339(),
+            "2024-11-29T18:00:00+00:00"
+        );
+    }
+
     #[test]
     fn test_get_calendar_query() {
         let query = CalendarQuery::new(AccountType::Paper).send().unwrap();
@@ -1,9 +1,9 @@
 use crate::request;
 
 use super::AccountType;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct MarketClock {
     pub timestamp: String,
     pub is_open: bool,
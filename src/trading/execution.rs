@@ -0,0 +1,913 @@
+//! Parent order execution algorithms. TWAP and VWAP work a quantity through a
+//! schedule of child orders, an iceberg shows only a small clip of a limit order at
+//! a time. Each can be paused, resumed, repriced or canceled through an
+//! `ExecutionControl` from another thread, and returns a `FillReport`.
+
+use super::{
+    order::{
+        delete_by_id, CreateOrderQuery, GetOrdersQuery, Order, OrderSide, OrderType, OrderWaiter,
+        ReplaceOrderQuery, TimeInForce,
+    },
+    AccountType,
+};
+use crate::{
+    market_data::stocks::{HistoricalBarsQuery, StockBar},
+    time::{new_york_offset, new_york_to_utc, parse_timestamp},
+    TimeFrame,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Error, ErrorKind},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// One child order of a schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slice {
+    pub at: DateTime<Utc>,
+    pub qty: f64,
+}
+
+/// Split a quantity by weights, in whole shares when the quantity is whole
+pub fn split_qty(total: f64, weights: &[f64]) -> Vec<f64> {
+    let sum: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+    let shares: Vec<f64> = weights
+        .iter()
+        .map(|weight| {
+            if sum > 0.0 {
+                total * weight.max(0.0) / sum
+            } else {
+                total / weights.len() as f64
+            }
+        })
+        .collect();
+    if total.fract() != 0.0 {
+        return shares;
+    }
+
+    // Largest remainder, so whole share slices still add up to the total
+    let mut whole: Vec<f64> = shares.iter().map(|share| share.floor()).collect();
+    let mut remainders: Vec<(usize, f64)> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, share)| (i, share - share.floor()))
+        .collect();
+    remainders.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let left = (total - whole.iter().sum::<f64>()).round() as usize;
+    for (i, _) in remainders.into_iter().take(left) {
+        whole[i] += 1.0;
+    }
+    whole
+}
+
+/// Equal slices spread evenly from `start` to `end`
+pub fn twap_schedule(
+    total_qty: f64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    slices: usize,
+) -> Vec<Slice> {
+    let slices = slices.max(1);
+    let interval = (end - start) / slices as i32;
+    split_qty(total_qty, &vec![1.0; slices])
+        .into_iter()
+        .enumerate()
+        .filter(|(_, qty)| *qty > 0.0)
+        .map(|(i, qty)| Slice {
+            at: start + interval * i as i32,
+            qty,
+        })
+        .collect()
+}
+
+/// Share of volume traded in each part of the New York trading day
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeProfile {
+    bucket_minutes: u32,
+    /// Volume by the bucket's starting minute of the day
+    volume: BTreeMap<u32, f64>,
+}
+
+impl VolumeProfile {
+    /// Build a profile from intraday bars, usually several days of minute bars
+    pub fn from_bars(bars: &[StockBar], bucket_minutes: u32) -> Result<Self, Error> {
+        if bucket_minutes == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Bucket Minutes Must Be Positive",
+            ));
+        }
+
+        let mut volume = BTreeMap::new();
+        for bar in bars {
+            let timestamp = parse_timestamp(&bar.t)?;
+            let local = timestamp.with_timezone(&new_york_offset(timestamp.date_naive()));
+            let minute = local.hour() * 60 + local.minute();
+            *volume.entry(minute - minute % bucket_minutes).or_default() += bar.v as f64;
+        }
+
+        Ok(Self {
+            bucket_minutes,
+            volume,
+        })
+    }
+
+    /// Build a profile from a symbol's minute bars between two dates
+    pub fn fetch(
+        symbol: &str,
+        start: &str,
+        end: &str,
+        bucket_minutes: u32,
+    ) -> Result<Self, ureq::Error> {
+        let bars = HistoricalBarsQuery::new(vec![symbol], TimeFrame::OneMinute)
+            .start(start)
+            .end(end)
+            .send()?;
+        let bars = bars.get(symbol).map(Vec::as_slice).unwrap_or_default();
+
+        Ok(Self::from_bars(bars, bucket_minutes)?)
+    }
+
+    /// Slices at the start of each bucket overlapping `start` to `end`, sized by the
+    /// bucket's share of volume. The window should sit within one trading day, and
+    /// with no volume to go by this falls back to TWAP.
+    pub fn schedule(&self, total_qty: f64, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Slice> {
+        let date = start
+            .with_timezone(&new_york_offset(start.date_naive()))
+            .date_naive();
+        let bucket = ChronoDuration::minutes(self.bucket_minutes as i64);
+
+        let mut times = Vec::new();
+        let mut weights = Vec::new();
+        for (minute, volume) in &self.volume {
+            let time = format!("{:02}:{:02}", minute / 60, minute % 60);
+            let Ok(bucket_start) = new_york_to_utc(date, &time) else {
+                continue;
+            };
+            let bucket_end = bucket_start + bucket;
+            if bucket_end <= start || bucket_start >= end {
+                continue;
+            }
+
+            // Buckets cut by the window only count the part inside it
+            let overlap = bucket_end.min(end) - bucket_start.max(start);
+            let share = overlap.num_seconds() as f64 / bucket.num_seconds() as f64;
+            times.push(bucket_start.max(start));
+            weights.push(volume * share);
+        }
+
+        if weights.iter().sum::<f64>() <= 0.0 {
+            let slices = ((end - start).num_minutes() / self.bucket_minutes as i64).max(1);
+            return twap_schedule(total_qty, start, end, slices as usize);
+        }
+        split_qty(total_qty, &weights)
+            .into_iter()
+            .zip(times)
+            .filter(|(qty, _)| *qty > 0.0)
+            .map(|(qty, at)| Slice { at, qty })
+            .collect()
+    }
+}
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const CANCELED: u8 = 2;
+
+/// A handle to pause, resume, reprice or cancel a running execution
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionControl {
+    state: Arc<AtomicU8>,
+    limit_price: Arc<Mutex<Option<String>>>,
+}
+
+impl ExecutionControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop sending new child orders, scheduled quantity is caught up on resume
+    pub fn pause(&self) {
+        let _ = self
+            .state
+            .compare_exchange(RUNNING, PAUSED, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        let _ = self
+            .state
+            .compare_exchange(PAUSED, RUNNING, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    /// Cancel the working child order and stop
+    pub fn cancel(&self) {
+        self.state.store(CANCELED, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == PAUSED
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == CANCELED
+    }
+
+    /// Change the limit price, the working child order is replaced at the new price.
+    /// Executions sending market orders ignore this.
+    pub fn reprice(&self, limit_price: &str) {
+        *self.limit_price.lock().expect("Lock is not poisoned") = Some(limit_price.to_string());
+    }
+
+    fn take_reprice(&self) -> Option<String> {
+        self.limit_price
+            .lock()
+            .expect("Lock is not poisoned")
+            .take()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    Completed,
+    Canceled,
+    /// The schedule ended with quantity left unfilled
+    Incomplete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildFill {
+    pub order_id: String,
+    pub qty: f64,
+    pub filled_qty: f64,
+    pub filled_avg_price: Option<f64>,
+    pub status: String,
+}
+
+/// What a parent order achieved and the child orders it used
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillReport {
+    pub target_qty: f64,
+    pub children: Vec<ChildFill>,
+    pub status: ExecutionStatus,
+}
+
+/// A request failed part way through. The working child order was canceled and the
+/// report holds what filled before the failure.
+#[derive(Debug)]
+pub struct ExecutionError {
+    pub report: FillReport,
+    pub error: Box<ureq::Error>,
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Execution stopped after filling {} of {}: {}",
+            self.report.filled_qty(),
+            self.report.target_qty,
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+fn parse(value: Option<&str>) -> Option<f64> {
+    value.and_then(|value| value.parse::<f64>().ok())
+}
+
+fn format_qty(qty: f64) -> String {
+    ((qty * 1e9).round() / 1e9).to_string()
+}
+
+impl FillReport {
+    pub fn new(target_qty: f64) -> Self {
+        Self {
+            target_qty,
+            children: Vec::new(),
+            status: ExecutionStatus::Incomplete,
+        }
+    }
+
+    /// Add or update a child order from its latest state
+    pub fn update(&mut self, order: &Order) {
+        let child = ChildFill {
+            order_id: order.id.clone(),
+            qty: parse(order.qty.as_deref()).unwrap_or_default(),
+            filled_qty: parse(order.filled_qty.as_deref()).unwrap_or_default(),
+            filled_avg_price: parse(order.filled_avg_price.as_deref()),
+            status: order.status.clone(),
+        };
+        match self.children.iter_mut().find(|c| c.order_id == order.id) {
+            Some(existing) => *existing = child,
+            None => self.children.push(child),
+        }
+    }
+
+    pub fn filled_qty(&self) -> f64 {
+        self.children.iter().map(|child| child.filled_qty).sum()
+    }
+
+    pub fn remaining_qty(&self) -> f64 {
+        (self.target_qty - self.filled_qty()).max(0.0)
+    }
+
+    /// Volume weighted price across every child fill
+    pub fn avg_price(&self) -> Option<f64> {
+        let filled = self.filled_qty();
+        if filled <= 0.0 {
+            return None;
+        }
+        let notional: f64 = self
+            .children
+            .iter()
+            .filter_map(|child| Some(child.filled_qty * child.filled_avg_price?))
+            .sum();
+        Some(notional / filled)
+    }
+}
+
+/// Shared plumbing for placing, refreshing and canceling child orders
+struct Children<'a> {
+    account_type: AccountType,
+    symbol: &'a str,
+    side: OrderSide,
+    limit_price: Option<String>,
+    report: FillReport,
+    working: Option<Order>,
+}
+
+impl<'a> Children<'a> {
+    fn place(&mut self, qty: f64) -> Result<(), ureq::Error> {
+        let qty = format_qty(qty);
+        let order = match &self.limit_price {
+            Some(limit_price) => CreateOrderQuery::new(
+                self.symbol,
+                self.side.clone(),
+                OrderType::Limit,
+                TimeInForce::Day,
+            )
+            .qty(&qty)
+            .limit_price(limit_price)
+            .send(self.account_type)?,
+            None => CreateOrderQuery::new(
+                self.symbol,
+                self.side.clone(),
+                OrderType::Market,
+                TimeInForce::Day,
+            )
+            .qty(&qty)
+            .send(self.account_type)?,
+        };
+        self.report.update(&order);
+        self.working = Some(order);
+        Ok(())
+    }
+
+    /// Refresh the working child, dropping it once it can no longer fill
+    fn refresh(&mut self) -> Result<(), ureq::Error> {
+        if let Some(working) = &self.working {
+            let order = GetOrdersQuery::new(self.account_type).get_by_id(&working.id, false)?;
+            self.report.update(&order);
+            self.working = order.is_open().then_some(order);
+        }
+        Ok(())
+    }
+
+    /// Cancel the working child and wait for its final fill
+    fn cancel(&mut self) -> Result<(), ureq::Error> {
+        if let Some(working) = self.working.take() {
+            match delete_by_id(&working.id, self.account_type) {
+                // Already filled or gone
+                Ok(_) | Err(ureq::Error::Status(404 | 422, _)) => {}
+                Err(error) => return Err(error),
+            }
+            let waiter = OrderWaiter::new(self.account_type);
+            match waiter.wait_until_terminal(&working.id, Duration::from_secs(10)) {
+                Ok(history) => self.report.update(&history.order),
+                Err(_) => self.report.update(&working),
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the working child at a new limit price, market orders have none to change
+    fn reprice(&mut self, limit_price: String) -> Result<(), ureq::Error> {
+        if self.limit_price.is_none() {
+            return Ok(());
+        }
+        let account_type = self.account_type;
+        self.replace_working(
+            limit_price,
+            |id| GetOrdersQuery::new(account_type).get_by_id(id, false),
+            |id, qty, limit_price| {
+                ReplaceOrderQuery::new(id)
+                    .qty(qty)
+                    .limit_price(limit_price)
+                    .send(account_type)
+            },
+        )?;
+        self.refresh()
+    }
+
+    /// Record what the working child has filled so far, then replace it with an order
+    /// for only the rest. `fetch` gets an order by id and `replace` takes the id,
+    /// quantity and limit price.
+    fn replace_working<F, R>(
+        &mut self,
+        limit_price: String,
+        fetch: F,
+        replace: R,
+    ) -> Result<(), ureq::Error>
+    where
+        F: FnOnce(&str) -> Result<Order, ureq::Error>,
+        R: FnOnce(&str, &str, &str) -> Result<Order, ureq::Error>,
+    {
+        if let Some(working) = &self.working {
+            let latest = fetch(&working.id)?;
+            self.report.update(&latest);
+            self.working = latest.is_open().then_some(latest);
+        }
+        let qty = self.working_qty();
+        if let Some(working) = self.working.as_ref().filter(|_| qty > 0.0) {
+            let order = replace(&working.id, &format_qty(qty), &limit_price)?;
+            self.report.update(&order);
+            self.working = Some(order);
+        }
+        self.limit_price = Some(limit_price);
+        Ok(())
+    }
+
+    /// Stop after a failed request, canceling the working child so nothing is left
+    /// live at the broker
+    fn abort(mut self, error: ureq::Error) -> ExecutionError {
+        let _ = self.cancel();
+        self.report.status = ExecutionStatus::Incomplete;
+        ExecutionError {
+            report: self.report,
+            error: Box::new(error),
+        }
+    }
+
+    /// Quantity of the working child still to fill
+    fn working_qty(&self) -> f64 {
+        self.working.as_ref().map_or(0.0, |order| {
+            let qty = parse(order.qty.as_deref()).unwrap_or_default();
+            let filled = parse(order.filled_qty.as_deref()).unwrap_or_default();
+            (qty - filled).max(0.0)
+        })
+    }
+}
+
+/// Works a parent order through a schedule of slices, TWAP or VWAP. Each slice
+/// replaces whatever is left of the previous child with one order for the rest.
+pub struct ScheduledExecution<'a> {
+    account_type: AccountType,
+    symbol: &'a str,
+    side: OrderSide,
+    schedule: Vec<Slice>,
+    limit_price: Option<&'a str>,
+    poll_interval: Duration,
+    final_timeout: Duration,
+    control: ExecutionControl,
+}
+
+impl<'a> ScheduledExecution<'a> {
+    pub fn new(
+        account_type: AccountType,
+        symbol: &'a str,
+        side: OrderSide,
+        schedule: Vec<Slice>,
+    ) -> Self {
+        Self {
+            account_type,
+            symbol,
+            side,
+            schedule,
+            limit_price: None,
+            poll_interval: Duration::from_secs(1),
+            final_timeout: Duration::from_secs(60),
+            control: ExecutionControl::new(),
+        }
+    }
+
+    pub fn twap(
+        account_type: AccountType,
+        symbol: &'a str,
+        side: OrderSide,
+        qty: f64,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        slices: usize,
+    ) -> Self {
+        Self::new(
+            account_type,
+            symbol,
+            side,
+            twap_schedule(qty, start, end, slices),
+        )
+    }
+
+    pub fn vwap(
+        account_type: AccountType,
+        symbol: &'a str,
+        side: OrderSide,
+        qty: f64,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        profile: &VolumeProfile,
+    ) -> Self {
+        Self::new(
+            account_type,
+            symbol,
+            side,
+            profile.schedule(qty, start, end),
+        )
+    }
+
+    /// Send limit children at this price instead of market orders
+    pub fn limit_price(mut self, limit_price: &'a str) -> Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// How long the last child may work after the final slice before it is canceled
+    pub fn final_timeout(mut self, final_timeout: Duration) -> Self {
+        self.final_timeout = final_timeout;
+        self
+    }
+
+    pub fn schedule(&self) -> &[Slice] {
+        &self.schedule
+    }
+
+    pub fn control(&self) -> ExecutionControl {
+        self.control.clone()
+    }
+
+    /// Sleep and poll until `until`, returning false if canceled on the way
+    fn wait(&self, children: &mut Children, until: DateTime<Utc>) -> Result<bool, ureq::Error> {
+        loop {
+            if self.control.is_canceled() {
+                return Ok(false);
+            }
+            if let Some(limit_price) = self.control.take_reprice() {
+                children.reprice(limit_price)?;
+            }
+            let now = Utc::now();
+            if now >= until && !self.control.is_paused() {
+                return Ok(true);
+            }
+            let left = (until - now).to_std().unwrap_or_default();
+            thread::sleep(if left.is_zero() {
+                self.poll_interval
+            } else {
+                left.min(self.poll_interval)
+            });
+            children.refresh()?;
+        }
+    }
+
+    /// Work the schedule, on a failed request the working child is canceled and the
+    /// report so far is returned with the error
+    pub fn run(self) -> Result<FillReport, ExecutionError> {
+        let target: f64 = self.schedule.iter().map(|slice| slice.qty).sum();
+        let mut children = Children {
+            account_type: self.account_type,
+            symbol: self.symbol,
+            side: self.side.clone(),
+            limit_price: self.limit_price.map(String::from),
+            report: FillReport::new(target),
+            working: None,
+        };
+
+        match self.work(&mut children) {
+            Ok(()) => Ok(children.report),
+            Err(error) => Err(children.abort(error)),
+        }
+    }
+
+    fn work(&self, children: &mut Children) -> Result<(), ureq::Error> {
+        let mut scheduled = 0.0;
+
+        for slice in &self.schedule {
+            if !self.wait(children, slice.at)? {
+                children.cancel()?;
+                children.report.status = ExecutionStatus::Canceled;
+                return Ok(());
+            }
+            scheduled += slice.qty;
+
+            // Roll what is left of the working child into this slice
+            children.cancel()?;
+            let qty = (scheduled - children.report.filled_qty()).max(0.0);
+            if qty > 0.0 {
+                children.place(qty)?;
+            }
+        }
+
+        let deadline = Utc::now()
+            + ChronoDuration::from_std(self.final_timeout).unwrap_or(ChronoDuration::zero());
+        while children.working_qty() > 0.0 && Utc::now() < deadline {
+            if !self.wait(children, Utc::now())? {
+                children.cancel()?;
+                children.report.status = ExecutionStatus::Canceled;
+                return Ok(());
+            }
+            thread::sleep(self.poll_interval);
+            children.refresh()?;
+        }
+        children.cancel()?;
+
+        children.report.status = if children.report.remaining_qty() > 0.0 {
+            ExecutionStatus::Incomplete
+        } else {
+            ExecutionStatus::Completed
+        };
+        Ok(())
+    }
+}
+
+/// A limit order shown a clip at a time, posting the next clip as each one fills
+pub struct IcebergExecution<'a> {
+    account_type: AccountType,
+    symbol: &'a str,
+    side: OrderSide,
+    total_qty: f64,
+    display_qty: f64,
+    limit_price: &'a str,
+    poll_interval: Duration,
+    control: ExecutionControl,
+}
+
+impl<'a> IcebergExecution<'a> {
+    pub fn new(
+        account_type: AccountType,
+        symbol: &'a str,
+        side: OrderSide,
+        total_qty: f64,
+        display_qty: f64,
+        limit_price: &'a str,
+    ) -> Self {
+        Self {
+            account_type,
+            symbol,
+            side,
+            total_qty,
+            display_qty,
+            limit_price,
+            poll_interval: Duration::from_secs(1),
+            control: ExecutionControl::new(),
+        }
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn control(&self) -> ExecutionControl {
+        self.control.clone()
+    }
+
+    /// Runs until the total quantity fills or the execution is canceled, on a failed
+    /// request the working clip is canceled and the report so far is returned with
+    /// the error
+    pub fn run(self) -> Result<FillReport, ExecutionError> {
+        let mut children = Children {
+            account_type: self.account_type,
+            symbol: self.symbol,
+            side: self.side.clone(),
+            limit_price: Some(self.limit_price.to_string()),
+            report: FillReport::new(self.total_qty),
+            working: None,
+        };
+
+        match self.work(&mut children) {
+            Ok(()) => Ok(children.report),
+            Err(error) => Err(children.abort(error)),
+        }
+    }
+
+    fn work(&self, children: &mut Children) -> Result<(), ureq::Error> {
+        loop {
+            if self.control.is_canceled() {
+                children.cancel()?;
+                children.report.status = ExecutionStatus::Canceled;
+                return Ok(());
+            }
+            if let Some(limit_price) = self.control.take_reprice() {
+                children.reprice(limit_price)?;
+            }
+
+            let remaining = children.report.remaining_qty() - children.working_qty();
+            if children.working.is_none() {
+                if remaining <= 0.0 {
+                    children.report.status = ExecutionStatus::Completed;
+                    return Ok(());
+                }
+                if !self.control.is_paused() {
+                    children.place(remaining.min(self.display_qty))?;
+                }
+            }
+
+            thread::sleep(self.poll_interval);
+            children.refresh()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::test_order;
+    use serde_json::json;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        parse_timestamp(time).unwrap()
+    }
+
+    fn bar(t: &str, v: f32) -> StockBar {
+        StockBar {
+            t: t.to_string(),
+            o: 100.0,
+            h: 100.0,
+            l: 100.0,
+            c: 100.0,
+            v,
+            n: 1,
+            vw: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_split_qty() {
+        assert_eq!(split_qty(10.0, &[1.0, 1.0, 1.0]), vec![4.0, 3.0, 3.0]);
+        assert_eq!(split_qty(3.0, &[1.0, 0.0, 2.0]), vec![1.0, 0.0, 2.0]);
+        assert_eq!(split_qty(1.5, &[1.0, 2.0]), vec![0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_twap_schedule() {
+        let schedule = twap_schedule(
+            100.0,
+            at("2024-07-01T14:00:00Z"),
+            at("2024-07-01T15:00:00Z"),
+            4,
+        );
+
+        assert_eq!(schedule.len(), 4);
+        assert_eq!(schedule[1].at, at("2024-07-01T14:15:00Z"));
+        assert!(schedule.iter().all(|slice| slice.qty == 25.0));
+    }
+
+    #[test]
+    fn test_vwap_schedule() {
+        // 09:30 and 10:00 New York buckets across two days, the open trading three times as much
+        let bars = vec![
+            bar("2024-07-01T13:30:00Z", 200.0),
+            bar("2024-07-01T14:00:00Z", 100.0),
+            bar("2024-07-02T13:45:00Z", 100.0),
+            bar("2024-07-02T14:10:00Z", 0.0),
+        ];
+        let profile = VolumeProfile::from_bars(&bars, 30).unwrap();
+
+        let schedule =
+            profile.schedule(40.0, at("2024-07-03T13:30:00Z"), at("2024-07-03T14:30:00Z"));
+        assert_eq!(
+            schedule,
+            vec![
+                Slice {
+                    at: at("2024-07-03T13:30:00Z"),
+                    qty: 30.0
+                },
+                Slice {
+                    at: at("2024-07-03T14:00:00Z"),
+                    qty: 10.0
+                },
+            ]
+        );
+
+        // Starting half way through the first bucket halves its weight
+        let schedule =
+            profile.schedule(50.0, at("2024-07-03T13:45:00Z"), at("2024-07-03T14:30:00Z"));
+        assert_eq!(schedule[0].at, at("2024-07-03T13:45:00Z"));
+        assert_eq!(schedule[0].qty, 30.0);
+    }
+
+    fn order(id: &str, filled: &str, price: &str) -> Order {
+        test_order(
+            id,
+            "filled",
+            json!({ "filled_qty": filled, "filled_avg_price": price }),
+        )
+    }
+
+    #[test]
+    fn test_fill_report() {
+        let mut report = FillReport::new(30.0);
+        report.update(&order("1", "5", "100"));
+        report.update(&order("1", "10", "100"));
+        report.update(&order("2", "10", "103"));
+
+        assert_eq!(report.children.len(), 2);
+        assert_eq!(report.filled_qty(), 20.0);
+        assert_eq!(report.remaining_qty(), 10.0);
+        assert_eq!(report.avg_price(), Some(101.5));
+    }
+
+    #[test]
+    fn test_market_children_ignore_reprice_and_abort_keeps_report() {
+        let mut children = Children {
+            account_type: AccountType::Paper,
+            symbol: "AAPL",
+            side: OrderSide::Buy,
+            limit_price: None,
+            report: FillReport::new(30.0),
+            working: Some(order("1", "4", "100")),
+        };
+        children.report.update(&order("1", "4", "100"));
+
+        // Nothing is sent for market children, the working order stays as it is
+        children.reprice("101".to_string()).unwrap();
+        assert_eq!(children.limit_price, None);
+        assert_eq!(children.working.as_ref().unwrap().id, "1");
+
+        children.working = None;
+        let error = children.abort(std::io::Error::other("poll failed").into());
+        assert_eq!(error.report.filled_qty(), 4.0);
+        assert_eq!(error.report.status, ExecutionStatus::Incomplete);
+    }
+
+    #[test]
+    fn test_reprice_replaces_only_the_unfilled_rest() {
+        let mut children = Children {
+            account_type: AccountType::Paper,
+            symbol: "AAPL",
+            side: OrderSide::Buy,
+            limit_price: Some("100".to_string()),
+            report: FillReport::new(10.0),
+            working: Some(test_order(
+                "1",
+                "partially_filled",
+                json!({ "filled_qty": "2" }),
+            )),
+        };
+
+        children
+            .replace_working(
+                "101".to_string(),
+                |id| {
+                    let fills = json!({ "filled_qty": "4", "filled_avg_price": "100" });
+                    Ok(test_order(id, "partially_filled", fills))
+                },
+                |id, qty, limit_price| {
+                    assert_eq!((id, qty, limit_price), ("1", "6", "101"));
+                    Ok(test_order(
+                        "2",
+                        "new",
+                        json!({ "qty": qty, "limit_price": limit_price }),
+                    ))
+                },
+            )
+            .unwrap();
+
+        assert_eq!(children.working.as_ref().unwrap().id, "2");
+        assert_eq!(children.limit_price.as_deref(), Some("101"));
+        assert_eq!(children.report.children.len(), 2);
+        assert_eq!(children.report.filled_qty(), 4.0);
+        assert_eq!(children.report.avg_price(), Some(100.0));
+        assert_eq!(children.working_qty(), 6.0);
+    }
+
+    #[test]
+    fn test_execution_control() {
+        let control = ExecutionControl::new();
+        let handle = control.clone();
+
+        handle.pause();
+        assert!(control.is_paused());
+        handle.resume();
+        assert!(!control.is_paused());
+
+        handle.reprice("101.5");
+        assert_eq!(control.take_reprice().as_deref(), Some("101.5"));
+        assert_eq!(control.take_reprice(), None);
+
+        handle.cancel();
+        handle.resume();
+        assert!(control.is_canceled());
+    }
+}
@@ -3,10 +3,17 @@ pub mod activities;
 pub mod assets;
 pub mod calendar;
 pub mod clock;
+pub mod execution;
+pub mod oms;
 pub mod order;
+pub mod pdt;
 pub mod portfolio;
 pub mod positions;
+pub mod rebalance;
+pub mod risk;
+pub mod watchlists;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccountType {
     Live,
     Paper,
@@ -0,0 +1,475 @@
+//! A local order management system, an in-memory book of orders kept in step
+//! with the broker through submissions, trade updates and reconciliation.
+
+use super::{
+    order::{CreateOrderQuery, GetOrdersQuery, Order, OrderStatus},
+    AccountType,
+};
+use crate::TradeUpdate;
+use std::{collections::HashMap, thread, time::Duration};
+
+/// Ways the local book and the broker disagreed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discrepancy {
+    /// The broker has an order the book did not know about
+    Untracked { id: String },
+    /// The book has an open order the broker does not know about
+    MissingAtBroker { id: String },
+    StatusMismatch {
+        id: String,
+        local: String,
+        broker: String,
+    },
+    FillMismatch {
+        id: String,
+        local: Option<String>,
+        broker: Option<String>,
+    },
+    /// The order left a status it should never leave
+    InvalidTransition {
+        id: String,
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OmsEvent {
+    Added {
+        id: String,
+    },
+    StatusChanged {
+        id: String,
+        from: String,
+        to: String,
+    },
+    Filled {
+        id: String,
+        filled_qty: String,
+    },
+    Replaced {
+        id: String,
+        replaced_by: String,
+    },
+    Discrepancy(Discrepancy),
+}
+
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    orders: HashMap<String, Order>,
+    client_order_ids: HashMap<String, String>,
+    legs: HashMap<String, Vec<String>>,
+    parents: HashMap<String, String>,
+}
+
+fn is_terminal(status: &str) -> bool {
+    status
+        .parse::<OrderStatus>()
+        .is_ok_and(|status| status.is_terminal())
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Order> {
+        self.orders.get(id)
+    }
+
+    pub fn get_by_client_order_id(&self, client_order_id: &str) -> Option<&Order> {
+        self.client_order_ids
+            .get(client_order_id)
+            .and_then(|id| self.orders.get(id))
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values()
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values().filter(|order| order.is_open())
+    }
+
+    /// Child legs of a bracket, OCO, OTO or multi-leg order
+    pub fn legs(&self, id: &str) -> Vec<&Order> {
+        self.legs
+            .get(id)
+            .map(|legs| legs.iter().filter_map(|id| self.orders.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn parent(&self, id: &str) -> Option<&Order> {
+        self.parents.get(id).and_then(|id| self.orders.get(id))
+    }
+
+    /// The order that currently stands in for `id`, following `replaced_by`
+    pub fn latest(&self, id: &str) -> Option<&Order> {
+        let mut order = self.orders.get(id)?;
+        while let Some(next) = order
+            .replaced_by
+            .as_ref()
+            .and_then(|id| self.orders.get(id))
+        {
+            order = next;
+        }
+        Some(order)
+    }
+
+    /// Every version of an order from the original through its replacements
+    pub fn replacement_chain(&self, id: &str) -> Vec<&Order> {
+        let mut first = match self.orders.get(id) {
+            Some(order) => order,
+            None => return Vec::new(),
+        };
+        while let Some(previous) = first.replaces.as_ref().and_then(|id| self.orders.get(id)) {
+            first = previous;
+        }
+
+        let mut chain = vec![first];
+        while let Some(next) = chain[chain.len() - 1]
+            .replaced_by
+            .as_ref()
+            .and_then(|id| self.orders.get(id))
+        {
+            chain.push(next);
+        }
+        chain
+    }
+
+    /// Record the latest state of an order and its legs, returning what changed. A
+    /// terminal order keeps its record, a later change of status is only reported,
+    /// see `reconcile` to take the broker's version regardless.
+    pub fn apply(&mut self, order: Order) -> Vec<OmsEvent> {
+        self.record(order, false)
+    }
+
+    /// Record an order, `authoritative` versions overwrite terminal orders too
+    fn record(&mut self, mut order: Order, authoritative: bool) -> Vec<OmsEvent> {
+        let mut events = Vec::new();
+
+        for leg in order.legs.take().unwrap_or_default() {
+            let leg_id = leg.id.clone();
+            events.extend(self.record(leg, authoritative));
+            self.parents.insert(leg_id.clone(), order.id.clone());
+            let legs = self.legs.entry(order.id.clone()).or_default();
+            if !legs.contains(&leg_id) {
+                legs.push(leg_id);
+            }
+        }
+
+        let id = order.id.clone();
+        match self.orders.get(&id) {
+            Some(previous) => {
+                if previous.status != order.status {
+                    if is_terminal(&previous.status) && !authoritative {
+                        events.push(OmsEvent::Discrepancy(Discrepancy::InvalidTransition {
+                            id,
+                            from: previous.status.clone(),
+                            to: order.status.clone(),
+                        }));
+                        return events;
+                    }
+                    events.push(OmsEvent::StatusChanged {
+                        id: id.clone(),
+                        from: previous.status.clone(),
+                        to: order.status.clone(),
+                    });
+                }
+                if previous.filled_qty != order.filled_qty {
+                    if let Some(filled_qty) = &order.filled_qty {
+                        events.push(OmsEvent::Filled {
+                            id: id.clone(),
+                            filled_qty: filled_qty.clone(),
+                        });
+                    }
+                }
+                if previous.replaced_by.is_none() {
+                    if let Some(replaced_by) = &order.replaced_by {
+                        events.push(OmsEvent::Replaced {
+                            id: id.clone(),
+                            replaced_by: replaced_by.clone(),
+                        });
+                    }
+                }
+            }
+            None => events.push(OmsEvent::Added { id: id.clone() }),
+        }
+
+        if let Some(client_order_id) = &order.client_order_id {
+            self.client_order_ids
+                .insert(client_order_id.clone(), id.clone());
+        }
+        self.orders.insert(id, order);
+        events
+    }
+
+    pub fn apply_trade_update(&mut self, update: &TradeUpdate) -> Vec<OmsEvent> {
+        self.apply(update.order.clone())
+    }
+
+    /// Compare orders fetched from the broker with the book, report where they
+    /// disagree, then take the broker's version, even over a terminal order. Each
+    /// difference is reported once, as a discrepancy rather than a change of status
+    /// or fill.
+    pub fn reconcile(&mut self, broker_orders: Vec<Order>) -> Vec<OmsEvent> {
+        let mut events = Vec::new();
+
+        for order in broker_orders {
+            let id = order.id.clone();
+            let (mut status_reported, mut fill_reported) = (false, false);
+            match self.orders.get(&id) {
+                Some(local) => {
+                    if local.status != order.status {
+                        events.push(OmsEvent::Discrepancy(Discrepancy::StatusMismatch {
+                            id: id.clone(),
+                            local: local.status.clone(),
+                            broker: order.status.clone(),
+                        }));
+                        status_reported = true;
+                    }
+                    if local.filled_qty != order.filled_qty {
+                        events.push(OmsEvent::Discrepancy(Discrepancy::FillMismatch {
+                            id: id.clone(),
+                            local: local.filled_qty.clone(),
+                            broker: order.filled_qty.clone(),
+                        }));
+                        fill_reported = true;
+                    }
+                }
+                None => events.push(OmsEvent::Discrepancy(Discrepancy::Untracked {
+                    id: id.clone(),
+                })),
+            }
+            events.extend(
+                self.record(order, true)
+                    .into_iter()
+                    .filter(|event| match event {
+                        OmsEvent::StatusChanged { id: changed, .. } => {
+                            !status_reported || *changed != id
+                        }
+                        OmsEvent::Filled { id: filled, .. } => !fill_reported || *filled != id,
+                        _ => true,
+                    }),
+            );
+        }
+
+        events
+    }
+
+    /// Submit an order and add it to the book
+    pub fn submit(
+        &mut self,
+        order: CreateOrderQuery,
+        account_type: AccountType,
+    ) -> Result<(Order, Vec<OmsEvent>), ureq::Error> {
+        let order = order.send(account_type)?;
+        let events = self.apply(order.clone());
+
+        Ok((order, events))
+    }
+
+    /// Reconcile against the broker's recent orders, then look up any open order
+    /// in the book the listing left out
+    pub fn sync(&mut self, account_type: AccountType) -> Result<Vec<OmsEvent>, ureq::Error> {
+        let broker_orders = GetOrdersQuery::new(account_type)
+            .status("all")
+            .nested(true)
+            .limit(500)
+            .send()?;
+        let listed: Vec<String> = broker_orders.iter().map(|order| order.id.clone()).collect();
+        let mut events = self.reconcile(broker_orders);
+
+        let unlisted: Vec<String> = self
+            .open_orders()
+            .filter(|order| !listed.contains(&order.id) && !self.parents.contains_key(&order.id))
+            .map(|order| order.id.clone())
+            .collect();
+        for id in unlisted {
+            match GetOrdersQuery::new(account_type).get_by_id(&id, true) {
+                Ok(order) => events.extend(self.reconcile(vec![order])),
+                Err(ureq::Error::Status(404, _)) => {
+                    events.push(OmsEvent::Discrepancy(Discrepancy::MissingAtBroker { id }))
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Sync on an interval, handing each batch of events to `on_events` until it returns false
+    pub fn sync_every<F: FnMut(&[OmsEvent]) -> bool>(
+        &mut self,
+        account_type: AccountType,
+        interval: Duration,
+        mut on_events: F,
+    ) -> Result<(), ureq::Error> {
+        loop {
+            let events = self.sync(account_type)?;
+            if !on_events(&events) {
+                return Ok(());
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::test_order;
+    use serde_json::json;
+
+    fn order(id: &str, status: &str, filled_qty: &str) -> Order {
+        test_order(id, status, json!({ "filled_qty": filled_qty }))
+    }
+
+    #[test]
+    fn test_order_lifecycle() {
+        let mut oms = OrderManager::new();
+
+        assert_eq!(
+            oms.apply(order("1", "new", "0")),
+            vec![OmsEvent::Added {
+                id: "1".to_string()
+            }]
+        );
+        assert_eq!(
+            oms.apply(order("1", "partially_filled", "4")),
+            vec![
+                OmsEvent::StatusChanged {
+                    id: "1".to_string(),
+                    from: "new".to_string(),
+                    to: "partially_filled".to_string()
+                },
+                OmsEvent::Filled {
+                    id: "1".to_string(),
+                    filled_qty: "4".to_string()
+                }
+            ]
+        );
+        assert_eq!(
+            oms.get_by_client_order_id("client-1")
+                .unwrap()
+                .filled_qty
+                .as_deref(),
+            Some("4")
+        );
+
+        oms.apply(order("1", "canceled", "4"));
+        assert_eq!(
+            oms.apply(order("1", "new", "4")),
+            vec![OmsEvent::Discrepancy(Discrepancy::InvalidTransition {
+                id: "1".to_string(),
+                from: "canceled".to_string(),
+                to: "new".to_string()
+            })]
+        );
+        assert_eq!(oms.get("1").unwrap().status, "canceled");
+        assert_eq!(oms.open_orders().count(), 0);
+    }
+
+    #[test]
+    fn test_legs_and_replacements() {
+        let mut oms = OrderManager::new();
+
+        let mut parent = order("1", "new", "0");
+        parent.legs = Some(vec![order("2", "held", "0"), order("3", "held", "0")]);
+        oms.apply(parent);
+        assert_eq!(oms.legs("1").len(), 2);
+        assert_eq!(oms.parent("3").unwrap().id, "1");
+
+        let mut replaced = order("1", "replaced", "0");
+        replaced.replaced_by = Some("4".to_string());
+        let mut replacement = order("4", "new", "0");
+        replacement.replaces = Some("1".to_string());
+        oms.apply(replacement);
+        assert!(oms.apply(replaced).contains(&OmsEvent::Replaced {
+            id: "1".to_string(),
+            replaced_by: "4".to_string()
+        }));
+
+        assert_eq!(oms.latest("1").unwrap().id, "4");
+        let chain: Vec<&str> = oms
+            .replacement_chain("4")
+            .iter()
+            .map(|order| order.id.as_str())
+            .collect();
+        assert_eq!(chain, vec!["1", "4"]);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let mut oms = OrderManager::new();
+        oms.apply(order("1", "new", "0"));
+
+        let events = oms.reconcile(vec![order("1", "filled", "10"), order("2", "new", "0")]);
+        assert_eq!(
+            events,
+            vec![
+                OmsEvent::Discrepancy(Discrepancy::StatusMismatch {
+                    id: "1".to_string(),
+                    local: "new".to_string(),
+                    broker: "filled".to_string()
+                }),
+                OmsEvent::Discrepancy(Discrepancy::FillMismatch {
+                    id: "1".to_string(),
+                    local: Some("0".to_string()),
+                    broker: Some("10".to_string())
+                }),
+                OmsEvent::Discrepancy(Discrepancy::Untracked {
+                    id: "2".to_string()
+                }),
+                OmsEvent::Added {
+                    id: "2".to_string()
+                },
+            ]
+        );
+        assert_eq!(oms.get("1").unwrap().status, "filled");
+        assert_eq!(oms.open_orders().count(), 1);
+    }
+
+    #[test]
+    fn test_reconcile_overrides_terminal_order() {
+        // The cancel was acknowledged locally but the order filled first
+        let mut oms = OrderManager::new();
+        oms.apply(order("1", "canceled", "0"));
+
+        let events = oms.reconcile(vec![order("1", "filled", "10")]);
+        assert_eq!(
+            events,
+            vec![
+                OmsEvent::Discrepancy(Discrepancy::StatusMismatch {
+                    id: "1".to_string(),
+                    local: "canceled".to_string(),
+                    broker: "filled".to_string()
+                }),
+                OmsEvent::Discrepancy(Discrepancy::FillMismatch {
+                    id: "1".to_string(),
+                    local: Some("0".to_string()),
+                    broker: Some("10".to_string())
+                }),
+            ]
+        );
+        assert_eq!(oms.get("1").unwrap().status, "filled");
+        assert_eq!(oms.get("1").unwrap().filled_qty.as_deref(), Some("10"));
+    }
+
+    #[test]
+    fn test_trade_update() {
+        let mut oms = OrderManager::new();
+        let update: TradeUpdate = serde_json::from_value(json!({
+            "event": "fill",
+            "order": order("1", "filled", "10"),
+            "timestamp": "2024-07-01T14:00:00Z",
+            "price": "100",
+            "qty": "10",
+            "position_qty": "10"
+        }))
+        .unwrap();
+
+        oms.apply_trade_update(&update);
+        assert_eq!(oms.get("1").unwrap().status, "filled");
+    }
+}
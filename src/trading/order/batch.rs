@@ -0,0 +1,284 @@
+use super::{delete_by_id, CancelStatus, CreateOrderQuery, GetOrdersQuery, Order, OrderSide};
+use crate::trading::AccountType;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Spaces requests evenly so a batch stays within a per minute limit
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / per_minute.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free request slot
+    fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().expect("Lock is not poisoned");
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// The outcome of canceling one order in a batch. HTTP errors are decoded into the
+/// status, only transport errors are left as errors.
+#[derive(Debug)]
+pub struct BatchCancelResult {
+    pub id: String,
+    pub result: Result<CancelStatus, ureq::Error>,
+}
+
+impl BatchCancelResult {
+    pub fn is_success(&self) -> bool {
+        matches!(&self.result, Ok(status) if status.is_success())
+    }
+}
+
+/// Which open orders to cancel, every set condition must match
+#[derive(Debug, Clone, Default)]
+pub struct CancelFilter<'a> {
+    symbol: Option<&'a str>,
+    side: Option<OrderSide>,
+    client_order_id_prefix: Option<&'a str>,
+}
+
+impl<'a> CancelFilter<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn symbol(mut self, symbol: &'a str) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    pub fn side(mut self, side: OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Match client order ids starting with this, e.g. a `ClientOrderIdGenerator` prefix
+    pub fn client_order_id_prefix(mut self, prefix: &'a str) -> Self {
+        self.client_order_id_prefix = Some(prefix);
+        self
+    }
+
+    pub fn matches(&self, order: &Order) -> bool {
+        self.symbol.is_none_or(|symbol| order.symbol == symbol)
+            && self
+                .side
+                .as_ref()
+                .is_none_or(|side| order.side == side.to_string())
+            && self.client_order_id_prefix.is_none_or(|prefix| {
+                order
+                    .client_order_id
+                    .as_deref()
+                    .is_some_and(|id| id.starts_with(prefix))
+            })
+    }
+}
+
+/// Submits and cancels many orders at once, a few requests at a time within a rate
+/// limit. Requests rejected for exceeding the rate limit are retried.
+pub struct BatchOrders {
+    account_type: AccountType,
+    concurrency: usize,
+    requests_per_minute: u32,
+    max_retries: u32,
+}
+
+impl BatchOrders {
+    /// Four requests at a time, up to Alpaca's default 200 per minute
+    pub fn new(account_type: AccountType) -> Self {
+        Self {
+            account_type,
+            concurrency: 4,
+            requests_per_minute: 200,
+            max_retries: 3,
+        }
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn requests_per_minute(mut self, requests_per_minute: u32) -> Self {
+        self.requests_per_minute = requests_per_minute;
+        self
+    }
+
+    /// Retries for a request rejected with 429 Too Many Requests
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Make a request, retrying while it is rate limited
+    fn attempt<R, F>(&self, limiter: &RateLimiter, f: F) -> Result<R, ureq::Error>
+    where
+        F: Fn() -> Result<R, ureq::Error>,
+    {
+        let mut attempts = 0;
+        loop {
+            limiter.acquire();
+            match f() {
+                Err(ureq::Error::Status(429, response)) if attempts < self.max_retries => {
+                    attempts += 1;
+                    let wait = response
+                        .header("Retry-After")
+                        .and_then(|seconds| seconds.parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                        .unwrap_or(Duration::from_millis(250 * attempts as u64));
+                    thread::sleep(wait);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Run `f` over every item on worker threads, keeping results in item order
+    fn run<T, R, F>(&self, items: &[T], f: F) -> Vec<Result<R, ureq::Error>>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> Result<R, ureq::Error> + Sync,
+    {
+        let limiter = RateLimiter::new(self.requests_per_minute);
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<R, ureq::Error>>>> =
+            Mutex::new(items.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(items.len()) {
+                scope.spawn(|| {
+                    let mut index = next.fetch_add(1, Ordering::SeqCst);
+                    while let Some(item) = items.get(index) {
+                        let result = self.attempt(&limiter, || f(item));
+                        results.lock().expect("Lock is not poisoned")[index] = Some(result);
+                        index = next.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .expect("Lock is not poisoned")
+            .into_iter()
+            .map(|result| result.expect("Every item was run"))
+            .collect()
+    }
+
+    /// Submit orders, returning each order's result in the same order
+    pub fn submit(&self, orders: &[CreateOrderQuery]) -> Vec<Result<Order, ureq::Error>> {
+        self.run(orders, |order| order.post(self.account_type, None))
+    }
+
+    /// Cancel orders by id
+    pub fn cancel(&self, ids: &[&str]) -> Vec<BatchCancelResult> {
+        self.run(ids, |id| delete_by_id(id, self.account_type))
+            .into_iter()
+            .zip(ids)
+            .map(|(result, id)| BatchCancelResult {
+                id: id.to_string(),
+                result: match result {
+                    Ok(response) => Ok(CancelStatus::from_code(response.status() as i32)),
+                    Err(ureq::Error::Status(code, _)) => Ok(CancelStatus::from_code(code as i32)),
+                    Err(error) => Err(error),
+                },
+            })
+            .collect()
+    }
+
+    /// Cancel the open orders matching a filter
+    pub fn cancel_matching(
+        &self,
+        filter: &CancelFilter,
+    ) -> Result<Vec<BatchCancelResult>, ureq::Error> {
+        let orders = GetOrdersQuery::new(self.account_type)
+            .status("open")
+            .limit(500)
+            .send()?;
+        let ids: Vec<&str> = orders
+            .iter()
+            .filter(|order| filter.matches(order))
+            .map(|order| order.id.as_str())
+            .collect();
+        Ok(self.cancel(&ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::test_order;
+    use serde_json::json;
+
+    #[test]
+    fn test_cancel_filter() {
+        let order = test_order(
+            "1",
+            "new",
+            json!({ "client_order_id": "momentum-20240701000000000-0" }),
+        );
+
+        assert!(CancelFilter::new().matches(&order));
+        assert!(CancelFilter::new()
+            .symbol("AAPL")
+            .side(OrderSide::Buy)
+            .client_order_id_prefix("momentum-")
+            .matches(&order));
+        assert!(!CancelFilter::new().symbol("MSFT").matches(&order));
+        assert!(!CancelFilter::new().side(OrderSide::Sell).matches(&order));
+        assert!(!CancelFilter::new()
+            .client_order_id_prefix("meanrev-")
+            .matches(&order));
+    }
+
+    #[test]
+    fn test_run_keeps_order_and_rate() {
+        let batch = BatchOrders::new(AccountType::Paper)
+            .concurrency(3)
+            .requests_per_minute(6000);
+        let items: Vec<i32> = (0..6).collect();
+
+        let start = Instant::now();
+        let results = batch.run(&items, |item| Ok(item * 2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let results: Vec<i32> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results, vec![0, 2, 4, 6, 8, 10]);
+    }
+
+    #[test]
+    fn test_retries_rate_limited_requests() {
+        let batch = BatchOrders::new(AccountType::Paper).requests_per_minute(6000);
+        let calls = AtomicUsize::new(0);
+
+        let results = batch.run(&[()], |_| {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                let response = ureq::Response::new(429, "Too Many Requests", "")?;
+                return Err(ureq::Error::Status(429, response));
+            }
+            Ok("sent")
+        });
+        assert_eq!(results[0].as_ref().ok(), Some(&"sent"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,574 @@
+//! Typed order builders that only take the fields each order type needs, so a limit
+//! order without a limit price or a trailing stop without a trail can't be built.
+//! Each converts into a plain `CreateOrderQuery`.
+
+use super::{
+    CreateOrderQuery, Order, OrderClass, OrderSide, OrderType, StopLoss, TakeProfit, TimeInForce,
+};
+use crate::trading::AccountType;
+
+/// Size of an order, either a number of shares or a dollar amount
+#[derive(Debug, Clone, Copy)]
+pub enum Quantity<'a> {
+    Qty(&'a str),
+    Notional(&'a str),
+}
+
+/// How far a trailing stop follows the price
+#[derive(Debug, Clone, Copy)]
+pub enum Trail<'a> {
+    Price(&'a str),
+    Percent(&'a str),
+}
+
+/// The exit order an OTO entry triggers
+#[derive(Debug)]
+pub enum OtoExit<'a> {
+    TakeProfit(TakeProfit<'a>),
+    StopLoss(StopLoss<'a>),
+}
+
+fn base<'a>(
+    symbol: &'a str,
+    side: OrderSide,
+    order_type: OrderType,
+    quantity: Quantity<'a>,
+    time_in_force: TimeInForce,
+) -> CreateOrderQuery<'a> {
+    let query = CreateOrderQuery::new(symbol, side, order_type, time_in_force);
+    match quantity {
+        Quantity::Qty(qty) => query.qty(qty),
+        Quantity::Notional(notional) => query.notional(notional),
+    }
+}
+
+/// Typed orders that can be sent as they are
+pub trait OrderRequest<'a>: Into<CreateOrderQuery<'a>> {
+    fn send(self, account_type: AccountType) -> Result<Order, ureq::Error> {
+        self.into().send(account_type)
+    }
+}
+
+/// Simple orders that can open a bracket or OTO order
+pub trait EntryOrder<'a>: Into<CreateOrderQuery<'a>> {}
+
+pub struct MarketOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> MarketOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+    ) -> Self {
+        Self {
+            query: base(
+                symbol.as_ref(),
+                side,
+                OrderType::Market,
+                quantity,
+                time_in_force,
+            ),
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+pub struct LimitOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> LimitOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+        limit_price: &'a str,
+    ) -> Self {
+        Self {
+            query: base(
+                symbol.as_ref(),
+                side,
+                OrderType::Limit,
+                quantity,
+                time_in_force,
+            )
+            .limit_price(limit_price),
+        }
+    }
+
+    /// Only day limit orders may trade in extended hours
+    pub fn extend_hours(mut self, extend_hours: bool) -> Self {
+        self.query = self.query.extend_hours(extend_hours);
+        self
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+pub struct StopOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> StopOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+        stop_price: &'a str,
+    ) -> Self {
+        Self {
+            query: base(
+                symbol.as_ref(),
+                side,
+                OrderType::Stop,
+                quantity,
+                time_in_force,
+            )
+            .stop_price(stop_price),
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+pub struct StopLimitOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> StopLimitOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+        stop_price: &'a str,
+        limit_price: &'a str,
+    ) -> Self {
+        Self {
+            query: base(
+                symbol.as_ref(),
+                side,
+                OrderType::StopLimit,
+                quantity,
+                time_in_force,
+            )
+            .stop_price(stop_price)
+            .limit_price(limit_price),
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+pub struct TrailingStopOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> TrailingStopOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+        trail: Trail<'a>,
+    ) -> Self {
+        let query = base(
+            symbol.as_ref(),
+            side,
+            OrderType::TrailingStop,
+            quantity,
+            time_in_force,
+        );
+        Self {
+            query: match trail {
+                Trail::Price(price) => query.trail_price(price),
+                Trail::Percent(percent) => query.trail_percent(percent),
+            },
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+/// An entry order with both a take profit and a stop loss exit
+pub struct BracketOrder<'a, E: EntryOrder<'a>> {
+    entry: E,
+    take_profit: TakeProfit<'a>,
+    stop_loss: StopLoss<'a>,
+}
+
+impl<'a, E: EntryOrder<'a>> BracketOrder<'a, E> {
+    pub fn new(entry: E, take_profit: TakeProfit<'a>, stop_loss: StopLoss<'a>) -> Self {
+        Self {
+            entry,
+            take_profit,
+            stop_loss,
+        }
+    }
+}
+
+/// A take profit limit and a stop loss where filling one cancels the other. It exits
+/// an open position, so a sell closes a long with the take profit above the stop.
+pub struct OcoOrder<'a> {
+    query: CreateOrderQuery<'a>,
+}
+
+impl<'a> OcoOrder<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
+        side: OrderSide,
+        quantity: Quantity<'a>,
+        time_in_force: TimeInForce,
+        take_profit: TakeProfit<'a>,
+        stop_loss: StopLoss<'a>,
+    ) -> Self {
+        Self {
+            query: base(
+                symbol.as_ref(),
+                side,
+                OrderType::Limit,
+                quantity,
+                time_in_force,
+            )
+            .order_class(OrderClass::OneCancelsOther)
+            .take_profit(take_profit)
+            .stop_loss(stop_loss),
+        }
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.query = self.query.client_order_id(client_order_id);
+        self
+    }
+}
+
+/// An entry order that submits a single exit once filled
+pub struct OtoOrder<'a, E: EntryOrder<'a>> {
+    entry: E,
+    exit: OtoExit<'a>,
+}
+
+impl<'a, E: EntryOrder<'a>> OtoOrder<'a, E> {
+    pub fn new(entry: E, exit: OtoExit<'a>) -> Self {
+        Self { entry, exit }
+    }
+}
+
+impl<'a> From<MarketOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: MarketOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a> From<LimitOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: LimitOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a> From<StopOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: StopOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a> From<StopLimitOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: StopLimitOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a> From<TrailingStopOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: TrailingStopOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a, E: EntryOrder<'a>> From<BracketOrder<'a, E>> for CreateOrderQuery<'a> {
+    fn from(order: BracketOrder<'a, E>) -> Self {
+        order
+            .entry
+            .into()
+            .order_class(OrderClass::Bracket)
+            .take_profit(order.take_profit)
+            .stop_loss(order.stop_loss)
+    }
+}
+
+impl<'a> From<OcoOrder<'a>> for CreateOrderQuery<'a> {
+    fn from(order: OcoOrder<'a>) -> Self {
+        order.query
+    }
+}
+
+impl<'a, E: EntryOrder<'a>> From<OtoOrder<'a, E>> for CreateOrderQuery<'a> {
+    fn from(order: OtoOrder<'a, E>) -> Self {
+        let query = order.entry.into().order_class(OrderClass::OneTriggersOther);
+        match order.exit {
+            OtoExit::TakeProfit(take_profit) => query.take_profit(take_profit),
+            OtoExit::StopLoss(stop_loss) => query.stop_loss(stop_loss),
+        }
+    }
+}
+
+impl<'a> EntryOrder<'a> for MarketOrder<'a> {}
+impl<'a> EntryOrder<'a> for LimitOrder<'a> {}
+impl<'a> EntryOrder<'a> for StopOrder<'a> {}
+impl<'a> EntryOrder<'a> for StopLimitOrder<'a> {}
+
+impl<'a> OrderRequest<'a> for MarketOrder<'a> {}
+impl<'a> OrderRequest<'a> for LimitOrder<'a> {}
+impl<'a> OrderRequest<'a> for StopOrder<'a> {}
+impl<'a> OrderRequest<'a> for StopLimitOrder<'a> {}
+impl<'a> OrderRequest<'a> for TrailingStopOrder<'a> {}
+impl<'a, E: EntryOrder<'a>> OrderRequest<'a> for BracketOrder<'a, E> {}
+impl<'a> OrderRequest<'a> for OcoOrder<'a> {}
+impl<'a, E: EntryOrder<'a>> OrderRequest<'a> for OtoOrder<'a, E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::{assets::test_asset, order::OrderContext};
+    use serde_json::{json, to_value, Value};
+
+    fn body<'a>(order: impl Into<CreateOrderQuery<'a>>) -> Value {
+        to_value(order.into()).unwrap()
+    }
+
+    #[test]
+    fn test_simple_orders_match_query() {
+        assert_eq!(
+            body(MarketOrder::new(
+                "AAPL",
+                OrderSide::Buy,
+                Quantity::Notional("100"),
+                TimeInForce::Day
+            )),
+            body(
+                CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Market, TimeInForce::Day)
+                    .notional("100")
+            )
+        );
+
+        assert_eq!(
+            body(
+                LimitOrder::new(
+                    "AAPL",
+                    OrderSide::Sell,
+                    Quantity::Qty("2"),
+                    TimeInForce::Day,
+                    "200"
+                )
+                .extend_hours(true)
+            ),
+            body(
+                CreateOrderQuery::new("AAPL", OrderSide::Sell, OrderType::Limit, TimeInForce::Day)
+                    .qty("2")
+                    .limit_price("200")
+                    .extend_hours(true)
+            )
+        );
+
+        assert_eq!(
+            body(TrailingStopOrder::new(
+                "AAPL",
+                OrderSide::Sell,
+                Quantity::Qty("1"),
+                TimeInForce::GoodTilCanceled,
+                Trail::Percent("5")
+            )),
+            body(
+                CreateOrderQuery::new(
+                    "AAPL",
+                    OrderSide::Sell,
+                    OrderType::TrailingStop,
+                    TimeInForce::GoodTilCanceled
+                )
+                .qty("1")
+                .trail_percent("5")
+            )
+        );
+    }
+
+    #[test]
+    fn test_advanced_orders_match_query() {
+        let entry = LimitOrder::new(
+            "AAPL",
+            OrderSide::Buy,
+            Quantity::Qty("1"),
+            TimeInForce::GoodTilCanceled,
+            "100",
+        );
+        assert_eq!(
+            body(BracketOrder::new(
+                entry,
+                TakeProfit::new("110"),
+                StopLoss::new("95", "94")
+            )),
+            body(
+                CreateOrderQuery::new(
+                    "AAPL",
+                    OrderSide::Buy,
+                    OrderType::Limit,
+                    TimeInForce::GoodTilCanceled
+                )
+                .qty("1")
+                .limit_price("100")
+                .order_class(OrderClass::Bracket)
+                .take_profit(TakeProfit::new("110"))
+                .stop_loss(StopLoss::new("95", "94"))
+            )
+        );
+
+        let entry = MarketOrder::new(
+            "AAPL",
+            OrderSide::Buy,
+            Quantity::Qty("1"),
+            TimeInForce::GoodTilCanceled,
+        );
+        let order = body(OtoOrder::new(
+            entry,
+            OtoExit::StopLoss(StopLoss::new("90", "89")),
+        ));
+        assert_eq!(order["order_class"], "oto");
+        assert_eq!(order["stop_loss"]["stop_price"], "90");
+        assert!(order.get("take_profit").is_none());
+
+        let order = body(OcoOrder::new(
+            "AAPL",
+            OrderSide::Sell,
+            Quantity::Qty("1"),
+            TimeInForce::GoodTilCanceled,
+            TakeProfit::new("110"),
+            StopLoss::new("95", "94"),
+        ));
+        assert_eq!(order["type"], "limit");
+        assert_eq!(order["order_class"], "oco");
+    }
+
+    #[test]
+    fn test_builders_pass_validation() {
+        let asset = test_asset(json!({}));
+        let context = OrderContext::new(&asset);
+        let validate = |order: CreateOrderQuery| order.validate(&context);
+        let limit = |side, price| {
+            LimitOrder::new(
+                "AAPL",
+                side,
+                Quantity::Qty("1"),
+                TimeInForce::GoodTilCanceled,
+                price,
+            )
+        };
+        let market = |side| {
+            MarketOrder::new(
+                "AAPL",
+                side,
+                Quantity::Qty("1"),
+                TimeInForce::GoodTilCanceled,
+            )
+        };
+
+        let orders: Vec<CreateOrderQuery> = vec![
+            market(OrderSide::Buy).into(),
+            limit(OrderSide::Sell, "100").into(),
+            StopOrder::new(
+                "AAPL",
+                OrderSide::Sell,
+                Quantity::Qty("1"),
+                TimeInForce::Day,
+                "95",
+            )
+            .into(),
+            StopLimitOrder::new(
+                "AAPL",
+                OrderSide::Buy,
+                Quantity::Notional("100"),
+                TimeInForce::Day,
+                "105",
+                "106",
+            )
+            .into(),
+            TrailingStopOrder::new(
+                "AAPL",
+                OrderSide::Sell,
+                Quantity::Qty("1"),
+                TimeInForce::Day,
+                Trail::Percent("5"),
+            )
+            .into(),
+            BracketOrder::new(
+                limit(OrderSide::Buy, "100"),
+                TakeProfit::new("110"),
+                StopLoss::new("95", "94"),
+            )
+            .into(),
+            BracketOrder::new(
+                limit(OrderSide::Sell, "100"),
+                TakeProfit::new("90"),
+                StopLoss::new("105", "106"),
+            )
+            .into(),
+            BracketOrder::new(
+                market(OrderSide::Buy),
+                TakeProfit::new("110"),
+                StopLoss::new("95", "94"),
+            )
+            .into(),
+            OcoOrder::new(
+                "AAPL",
+                OrderSide::Sell,
+                Quantity::Qty("1"),
+                TimeInForce::GoodTilCanceled,
+                TakeProfit::new("110"),
+                StopLoss::new("95", "94"),
+            )
+            .into(),
+            OcoOrder::new(
+                "AAPL",
+                OrderSide::Buy,
+                Quantity::Qty("1"),
+                TimeInForce::GoodTilCanceled,
+                TakeProfit::new("90"),
+                StopLoss::new("105", "106"),
+            )
+            .into(),
+            OtoOrder::new(
+                limit(OrderSide::Buy, "100"),
+                OtoExit::StopLoss(StopLoss::new("90", "89")),
+            )
+            .into(),
+            OtoOrder::new(
+                limit(OrderSide::Sell, "100"),
+                OtoExit::TakeProfit(TakeProfit::new("90")),
+            )
+            .into(),
+        ];
+
+        for order in orders {
+            let body = to_value(&order).unwrap();
+            assert_eq!(validate(order), Ok(()), "{body}");
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use super::{CreateOrderQuery, GetOrdersQuery, Order};
+use crate::trading::AccountType;
+use chrono::Utc;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Alpaca rejects client order ids longer than this
+const MAX_CLIENT_ORDER_ID_LEN: usize = 128;
+
+/// Generates unique client order ids tagged with a prefix such as a strategy name,
+/// in the form `{prefix}-{timestamp}-{sequence}`
+#[derive(Debug)]
+pub struct ClientOrderIdGenerator {
+    prefix: String,
+    sequence: AtomicU64,
+}
+
+impl ClientOrderIdGenerator {
+    /// Long prefixes are cut short so ids stay within the API's limit
+    pub fn new(prefix: &str) -> Self {
+        let prefix: String = prefix.chars().take(MAX_CLIENT_ORDER_ID_LEN - 40).collect();
+        Self {
+            prefix,
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn next_id(&self) -> String {
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+        let timestamp = Utc::now().format("%Y%m%d%H%M%S%3f");
+        format!("{}-{}-{}", self.prefix, timestamp, sequence)
+    }
+
+    /// Whether an order id was made by a generator with this prefix
+    pub fn owns(&self, client_order_id: &str) -> bool {
+        client_order_id
+            .strip_prefix(&self.prefix)
+            .is_some_and(|rest| rest.starts_with('-'))
+    }
+}
+
+/// Errors where the order may or may not have reached the broker
+fn is_ambiguous(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Transport(_) => true,
+        ureq::Error::Status(status, _) => *status >= 500,
+    }
+}
+
+/// Shared by every order sent without an id, so ids made in the same millisecond
+/// still differ by sequence
+fn default_generator() -> &'static ClientOrderIdGenerator {
+    static GENERATOR: OnceLock<ClientOrderIdGenerator> = OnceLock::new();
+    GENERATOR.get_or_init(|| ClientOrderIdGenerator::new("order"))
+}
+
+/// Post an order, retrying ambiguous failures after checking whether an earlier
+/// attempt reached the broker
+fn post_idempotent<P, L>(
+    client_order_id: &str,
+    max_retries: u32,
+    backoff: Duration,
+    post: P,
+    lookup: L,
+) -> Result<Order, ureq::Error>
+where
+    P: Fn() -> Result<Order, ureq::Error>,
+    L: Fn(&str) -> Result<Order, ureq::Error>,
+{
+    let mut attempts = 0;
+    loop {
+        let error = match post() {
+            Ok(order) => return Ok(order),
+            Err(error) => error,
+        };
+        // A retry rejected as a duplicate means an earlier attempt got through
+        let duplicate = attempts > 0 && matches!(error, ureq::Error::Status(422, _));
+        if !is_ambiguous(&error) && !duplicate {
+            return Err(error);
+        }
+
+        match lookup(client_order_id) {
+            Ok(order) => return Ok(order),
+            Err(ureq::Error::Status(404, _)) => {}
+            Err(lookup) if is_ambiguous(&lookup) => {}
+            Err(lookup) => return Err(lookup),
+        }
+
+        if attempts >= max_retries || duplicate {
+            return Err(error);
+        }
+        attempts += 1;
+        thread::sleep(backoff * attempts);
+    }
+}
+
+impl<'a> CreateOrderQuery<'a> {
+    /// Send the order, retrying up to `max_retries` times on timeouts and server errors.
+    /// Before each retry the order is looked up by its client order id, so an order
+    /// that did reach the broker is returned instead of being submitted twice. An id
+    /// is generated when the order has none. Each request gives up after `timeout`.
+    pub fn send_idempotent(
+        mut self,
+        account_type: AccountType,
+        max_retries: u32,
+        timeout: Duration,
+    ) -> Result<Order, ureq::Error> {
+        let client_order_id = self
+            .client_order_id
+            .get_or_insert_with(|| default_generator().next_id())
+            .clone();
+
+        post_idempotent(
+            &client_order_id,
+            max_retries,
+            Duration::from_millis(250),
+            || self.post(account_type, Some(timeout)),
+            |id| {
+                GetOrdersQuery::new(account_type)
+                    .timeout(timeout)
+                    .get_by_client_order_id(id)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::{test_order, OrderSide, OrderType, TimeInForce};
+    use serde_json::json;
+
+    #[test]
+    fn test_generated_ids_are_unique_and_tagged() {
+        let generator = ClientOrderIdGenerator::new("momentum");
+        let first = generator.next_id();
+        let second = generator.next_id();
+
+        assert_ne!(first, second);
+        assert!(first.starts_with("momentum-"));
+        assert!(second.ends_with("-1"));
+        assert!(generator.owns(&first));
+        assert!(!generator.owns("momentum2-20240701000000000-0"));
+    }
+
+    #[test]
+    fn test_long_prefix_fits_limit() {
+        let generator = ClientOrderIdGenerator::new(&"x".repeat(200));
+        assert!(generator.next_id().len() <= MAX_CLIENT_ORDER_ID_LEN);
+    }
+
+    #[test]
+    fn test_generate_keeps_manual_id() {
+        let generator = ClientOrderIdGenerator::new("strategy");
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Market, TimeInForce::Day)
+                .client_order_id("manual")
+                .generate_client_order_id(&generator);
+        assert_eq!(order.client_order_id.as_deref(), Some("manual"));
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Market, TimeInForce::Day)
+                .generate_client_order_id(&generator);
+        assert!(generator.owns(order.client_order_id.as_deref().unwrap()));
+    }
+
+    #[test]
+    fn test_default_ids_are_unique() {
+        assert_ne!(default_generator().next_id(), default_generator().next_id());
+    }
+
+    fn status(code: u16) -> ureq::Error {
+        ureq::Error::Status(code, ureq::Response::new(code, "", "").unwrap())
+    }
+
+    #[test]
+    fn test_timeout_finds_order_that_got_through() {
+        let posts = AtomicU64::new(0);
+        let result = post_idempotent(
+            "client-1",
+            3,
+            Duration::ZERO,
+            || {
+                posts.fetch_add(1, Ordering::SeqCst);
+                Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into())
+            },
+            |id| {
+                assert_eq!(id, "client-1");
+                Ok(test_order("1", "new", json!({})))
+            },
+        );
+
+        assert_eq!(result.unwrap().id, "1");
+        assert_eq!(posts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_duplicate_retry_returns_existing_order() {
+        // The first post fails with a server error but still reaches the broker, the
+        // lookup misses it at first and the retry is rejected as a duplicate
+        let posts = AtomicU64::new(0);
+        let lookups = AtomicU64::new(0);
+        let result = post_idempotent(
+            "client-1",
+            3,
+            Duration::ZERO,
+            || match posts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(status(500)),
+                _ => Err(status(422)),
+            },
+            |_| match lookups.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(status(404)),
+                _ => Ok(test_order("1", "new", json!({}))),
+            },
+        );
+
+        assert_eq!(result.unwrap().id, "1");
+        assert_eq!(posts.load(Ordering::SeqCst), 2);
+
+        // A duplicate on the first attempt is a real rejection
+        let result = post_idempotent(
+            "client-1",
+            3,
+            Duration::ZERO,
+            || Err(status(422)),
+            |_| Ok(test_order("1", "new", json!({}))),
+        );
+        assert!(matches!(result, Err(ureq::Error::Status(422, _))));
+    }
+}
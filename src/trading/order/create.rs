@@ -1,55 +1,56 @@
-use super::{Order, OrderSide};
+use super::{ClientOrderIdGenerator, Order, OrderSide};
 use crate::{request, trading::AccountType};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateOrderQuery<'a> {
-    symbol: &'a str,
-    side: String,
-    r#type: String,
-    time_in_force: String,
-    extend_hours: bool,
+    pub(crate) symbol: &'a str,
+    pub(crate) side: String,
+    pub(crate) r#type: String,
+    pub(crate) time_in_force: String,
+    pub(crate) extend_hours: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    qty: Option<&'a str>,
+    pub(crate) qty: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    notional: Option<&'a str>,
+    pub(crate) notional: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    limit_price: Option<&'a str>,
+    pub(crate) limit_price: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    stop_price: Option<&'a str>,
+    pub(crate) stop_price: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    trail_price: Option<&'a str>,
+    pub(crate) trail_price: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    trail_percent: Option<&'a str>,
+    pub(crate) trail_percent: Option<&'a str>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    client_order_id: Option<&'a str>,
+    pub(crate) client_order_id: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    order_class: Option<String>,
+    pub(crate) order_class: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    take_profit: Option<TakeProfit<'a>>,
+    pub(crate) take_profit: Option<TakeProfit<'a>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    stop_loss: Option<StopLoss<'a>>,
+    pub(crate) stop_loss: Option<StopLoss<'a>>,
 }
 
 impl<'a> CreateOrderQuery<'a> {
-    pub fn new(
-        symbol: &'a str,
+    pub fn new<S: AsRef<str> + ?Sized>(
+        symbol: &'a S,
         side: OrderSide,
         order_type: OrderType,
         time_in_force: TimeInForce,
     ) -> Self {
         Self {
-            symbol,
+            symbol: symbol.as_ref(),
             side: side.to_string(),
             r#type: order_type.to_string(),
             time_in_force: time_in_force.to_string(),
@@ -103,7 +104,15 @@ impl<'a> CreateOrderQuery<'a> {
     }
 
     pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
-        self.client_order_id = Some(client_order_id);
+        self.client_order_id = Some(client_order_id.to_string());
+        self
+    }
+
+    /// Tag the order with the generator's next id, unless it already has one
+    pub fn generate_client_order_id(mut self, generator: &ClientOrderIdGenerator) -> Self {
+        if self.client_order_id.is_none() {
+            self.client_order_id = Some(generator.next_id());
+        }
         self
     }
 
@@ -123,14 +132,24 @@ impl<'a> CreateOrderQuery<'a> {
     }
 
     pub fn send(self, account_type: AccountType) -> Result<Order, ureq::Error> {
+        self.post(account_type, None)
+    }
+
+    pub(crate) fn post(
+        &self,
+        account_type: AccountType,
+        timeout: Option<Duration>,
+    ) -> Result<Order, ureq::Error> {
         let url = match account_type {
             AccountType::Live => "https://api.alpaca.markets/v2/orders",
             AccountType::Paper => "https://paper-api.alpaca.markets/v2/orders",
         };
 
-        let response = request("POST", url)
-            .set("Content-Type", "application/json")
-            .send_json(&self)?;
+        let mut request = request("POST", url).set("Content-Type", "application/json");
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send_json(self)?;
 
         let order = response.into_json()?;
         Ok(order)
@@ -187,6 +206,7 @@ pub enum OrderClass {
     Bracket,
     OneCancelsOther,
     OneTriggersOther,
+    MultiLeg,
 }
 
 impl ToString for OrderClass {
@@ -196,6 +216,7 @@ impl ToString for OrderClass {
             OrderClass::Bracket => "bracket".to_string(),
             OrderClass::OneCancelsOther => "oco".to_string(),
             OrderClass::OneTriggersOther => "oto".to_string(),
+            OrderClass::MultiLeg => "mleg".to_string(),
         }
     }
 }
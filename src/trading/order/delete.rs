@@ -1,14 +1,45 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use ureq::Response;
 
 use crate::{request, trading::AccountType};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct DeleteOrderResult {
     pub id: String,
     pub status: i32,
 }
 
+/// The outcome of canceling one order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelStatus {
+    Canceled,
+    NotFound,
+    /// The order can no longer be canceled, e.g. it already filled
+    NotCancelable,
+    Failed(i32),
+}
+
+impl CancelStatus {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            200..=299 => CancelStatus::Canceled,
+            404 => CancelStatus::NotFound,
+            422 => CancelStatus::NotCancelable,
+            code => CancelStatus::Failed(code),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        *self == CancelStatus::Canceled
+    }
+}
+
+impl DeleteOrderResult {
+    pub fn cancel_status(&self) -> CancelStatus {
+        CancelStatus::from_code(self.status)
+    }
+}
+
 pub fn delete_all_orders(account_type: AccountType) -> Result<Vec<DeleteOrderResult>, ureq::Error> {
     let url = match account_type {
         AccountType::Live => "https://api.alpaca.markets/v2/orders",
@@ -31,6 +62,18 @@ pub fn delete_by_id(id: &str, account_type: AccountType) -> Result<Response, ure
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cancel_status() {
+        let result = |status| DeleteOrderResult {
+            id: "1".to_string(),
+            status,
+        };
+        assert!(result(200).cancel_status().is_success());
+        assert_eq!(result(404).cancel_status(), CancelStatus::NotFound);
+        assert_eq!(result(422).cancel_status(), CancelStatus::NotCancelable);
+        assert_eq!(result(500).cancel_status(), CancelStatus::Failed(500));
+    }
+
     #[test]
     fn test_delete_all_orders() {
         //! Will fail if there are no orders
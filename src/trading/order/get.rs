@@ -1,5 +1,6 @@
 use super::{AllOrders, Order};
 use crate::{request, trading::AccountType};
+use std::time::Duration;
 
 pub struct GetOrdersQuery<'a> {
     pub url: &'a str,
@@ -11,6 +12,7 @@ pub struct GetOrdersQuery<'a> {
     pub nested: Option<bool>,
     pub symbols: Option<Vec<&'a str>>,
     pub side: Option<&'a str>,
+    pub timeout: Option<Duration>,
 }
 
 impl<'a> GetOrdersQuery<'a> {
@@ -28,6 +30,7 @@ impl<'a> GetOrdersQuery<'a> {
             nested: None,
             symbols: None,
             side: None,
+            timeout: None,
         }
     }
 
@@ -71,7 +74,21 @@ impl<'a> GetOrdersQuery<'a> {
         self
     }
 
-    fn build(self) -> String {
+    /// Give up on a request that takes longer than this
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn get(&self, route: &str) -> ureq::Request {
+        let request = request("GET", route);
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
+    fn build(&self) -> String {
         let mut query = String::new();
         if let Some(status) = self.status {
             query.push_str(&format!("&status={}", status));
@@ -91,7 +108,7 @@ impl<'a> GetOrdersQuery<'a> {
         if let Some(nested) = self.nested {
             query.push_str(&format!("&nested={}", nested));
         }
-        if let Some(symbols) = self.symbols {
+        if let Some(symbols) = &self.symbols {
             query.push_str(&format!("&symbols={}", symbols.join(",")));
         }
         if let Some(side) = self.side {
@@ -103,14 +120,25 @@ impl<'a> GetOrdersQuery<'a> {
 
     pub fn get_by_id(self, id: &'a str, nested: bool) -> Result<Order, ureq::Error> {
         let route = format!("{}/{}?&nested={}", self.url, id, nested);
-        let response = request("GET", &route).call()?;
+        let response = self.get(&route).call()?;
         let orders: Order = response.into_json()?;
         Ok(orders)
     }
 
+    /// Look up an order by the `client_order_id` it was submitted with
+    pub fn get_by_client_order_id(self, client_order_id: &'a str) -> Result<Order, ureq::Error> {
+        let route = format!("{}:by_client_order_id", self.url);
+        let response = self
+            .get(&route)
+            .query("client_order_id", client_order_id)
+            .call()?;
+        let order: Order = response.into_json()?;
+        Ok(order)
+    }
+
     pub fn send(self) -> Result<AllOrders, ureq::Error> {
         let route = self.build();
-        let response = request("GET", &route).call()?;
+        let response = self.get(&route).call()?;
         let orders: AllOrders = response.into_json()?;
         Ok(orders)
     }
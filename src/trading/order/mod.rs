@@ -1,5 +1,14 @@
+mod batch;
+pub use batch::*;
+
+mod builders;
+pub use builders::*;
+
+mod client_order_id;
+pub use client_order_id::*;
+
 mod create;
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 pub use create::*;
 
@@ -9,15 +18,24 @@ pub use delete::*;
 mod get;
 pub use get::*;
 
+mod multi_leg;
+pub use multi_leg::*;
+
 mod replace;
 pub use replace::*;
 
+mod validate;
+pub use validate::*;
+
+mod wait;
+pub use wait::*;
+
 use serde::{Deserialize, Serialize};
 
 pub type AllOrders = Vec<Order>;
 
 /// API object for an Order
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Order {
     pub id: String,
     pub client_order_id: Option<String>,
@@ -51,9 +69,41 @@ pub struct Order {
     pub trail_percent: Option<String>,
     pub trail_price: Option<String>,
     pub hwm: Option<String>,
+    pub ratio_qty: Option<String>,
+    pub position_intent: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl Order {
+    /// Whether the order can still fill, unknown statuses count as open
+    pub fn is_open(&self) -> bool {
+        self.status
+            .parse::<OrderStatus>()
+            .map_or(true, |status| !status.is_terminal())
+    }
+}
+
+/// A 10 share AAPL limit buy for tests, with any fields in `overrides` replaced
+#[cfg(test)]
+pub(crate) fn test_order(id: &str, status: &str, overrides: serde_json::Value) -> Order {
+    let mut order = serde_json::json!({
+        "id": id,
+        "client_order_id": format!("client-{id}"),
+        "symbol": "AAPL",
+        "qty": "10",
+        "filled_qty": "0",
+        "order_type": "limit",
+        "type": "limit",
+        "side": "buy",
+        "status": status,
+        "extended_hours": false
+    });
+    if let (Some(order), Some(overrides)) = (order.as_object_mut(), overrides.as_object()) {
+        order.extend(overrides.clone());
+    }
+    serde_json::from_value(order).expect("Test order is valid")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum OrderSide {
     Buy,
     Sell,
@@ -81,3 +131,91 @@ impl FromStr for OrderSide {
         }
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    DoneForDay,
+    Canceled,
+    Expired,
+    Replaced,
+    PendingCancel,
+    PendingReplace,
+    PendingNew,
+    Accepted,
+    AcceptedForBidding,
+    Stopped,
+    Rejected,
+    Suspended,
+    Calculated,
+    Held,
+}
+
+impl OrderStatus {
+    /// Statuses an order never leaves
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Expired
+                | OrderStatus::Replaced
+                | OrderStatus::Rejected
+        )
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            OrderStatus::New => "new",
+            OrderStatus::PartiallyFilled => "partially_filled",
+            OrderStatus::Filled => "filled",
+            OrderStatus::DoneForDay => "done_for_day",
+            OrderStatus::Canceled => "canceled",
+            OrderStatus::Expired => "expired",
+            OrderStatus::Replaced => "replaced",
+            OrderStatus::PendingCancel => "pending_cancel",
+            OrderStatus::PendingReplace => "pending_replace",
+            OrderStatus::PendingNew => "pending_new",
+            OrderStatus::Accepted => "accepted",
+            OrderStatus::AcceptedForBidding => "accepted_for_bidding",
+            OrderStatus::Stopped => "stopped",
+            OrderStatus::Rejected => "rejected",
+            OrderStatus::Suspended => "suspended",
+            OrderStatus::Calculated => "calculated",
+            OrderStatus::Held => "held",
+        };
+        write!(f, "{status}")
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new" => Ok(OrderStatus::New),
+            "partially_filled" => Ok(OrderStatus::PartiallyFilled),
+            "filled" => Ok(OrderStatus::Filled),
+            "done_for_day" => Ok(OrderStatus::DoneForDay),
+            "canceled" => Ok(OrderStatus::Canceled),
+            "expired" => Ok(OrderStatus::Expired),
+            "replaced" => Ok(OrderStatus::Replaced),
+            "pending_cancel" => Ok(OrderStatus::PendingCancel),
+            "pending_replace" => Ok(OrderStatus::PendingReplace),
+            "pending_new" => Ok(OrderStatus::PendingNew),
+            "accepted" => Ok(OrderStatus::Accepted),
+            "accepted_for_bidding" => Ok(OrderStatus::AcceptedForBidding),
+            "stopped" => Ok(OrderStatus::Stopped),
+            "rejected" => Ok(OrderStatus::Rejected),
+            "suspended" => Ok(OrderStatus::Suspended),
+            "calculated" => Ok(OrderStatus::Calculated),
+            "held" => Ok(OrderStatus::Held),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use super::{Order, OrderClass, OrderSide, OrderType, TimeInForce};
+use crate::{request, trading::AccountType, OccSymbol, OptionType};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, io::Error};
+
+/// Alpaca accepts at most four legs in one order
+const MAX_LEGS: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionIntent {
+    BuyToOpen,
+    BuyToClose,
+    SellToOpen,
+    SellToClose,
+}
+
+/// One leg of a multi-leg order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderLeg<'a> {
+    pub symbol: &'a str,
+    pub ratio_qty: String,
+    pub side: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_intent: Option<PositionIntent>,
+}
+
+impl<'a> OrderLeg<'a> {
+    pub fn new<S: AsRef<str> + ?Sized>(symbol: &'a S, side: OrderSide, ratio_qty: u32) -> Self {
+        Self {
+            symbol: symbol.as_ref(),
+            ratio_qty: ratio_qty.to_string(),
+            side: side.to_string(),
+            position_intent: None,
+        }
+    }
+
+    pub fn position_intent(mut self, position_intent: PositionIntent) -> Self {
+        self.position_intent = Some(position_intent);
+        self
+    }
+
+    /// A leg opening a position, with the matching position intent
+    fn open(symbol: &'a OccSymbol, side: OrderSide, ratio_qty: u32) -> Self {
+        let intent = match side {
+            OrderSide::Buy => PositionIntent::BuyToOpen,
+            OrderSide::Sell => PositionIntent::SellToOpen,
+        };
+        Self::new(symbol, side, ratio_qty).position_intent(intent)
+    }
+}
+
+/// Order of up to four option legs filled together, `order_class=mleg`
+///
+/// `qty` is the number of strategy units and each leg trades `qty * ratio_qty`
+/// contracts. The strategy constructors validate their legs and open positions,
+/// use [`MultiLegOrderQuery::new`] with custom legs for anything else.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MultiLegOrderQuery<'a> {
+    order_class: String,
+    qty: &'a str,
+    r#type: String,
+    time_in_force: String,
+    legs: Vec<OrderLeg<'a>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_price: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_order_id: Option<&'a str>,
+}
+
+impl<'a> MultiLegOrderQuery<'a> {
+    /// Validates that there are two to four legs on distinct symbols, and that the
+    /// ratios are positive and in lowest terms as alpaca requires
+    pub fn new(legs: Vec<OrderLeg<'a>>, qty: &'a str) -> Result<Self, Error> {
+        if !(2..=MAX_LEGS).contains(&legs.len()) {
+            return Err(Error::other(format!(
+                "Multi-Leg Orders Need 2 To {MAX_LEGS} Legs"
+            )));
+        }
+
+        let mut symbols = HashSet::new();
+        if !legs.iter().all(|leg| symbols.insert(leg.symbol)) {
+            return Err(Error::other("Multi-Leg Order Repeats A Symbol"));
+        }
+
+        let ratios = legs
+            .iter()
+            .map(|leg| leg.ratio_qty.parse::<u32>().ok().filter(|ratio| *ratio > 0))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| Error::other("Leg Ratios Must Be Positive Integers"))?;
+        if ratios.iter().copied().fold(0, gcd) != 1 {
+            return Err(Error::other("Leg Ratios Must Be In Lowest Terms"));
+        }
+
+        Ok(Self {
+            order_class: OrderClass::MultiLeg.to_string(),
+            qty,
+            r#type: OrderType::Market.to_string(),
+            time_in_force: TimeInForce::Day.to_string(),
+            legs,
+            limit_price: None,
+            client_order_id: None,
+        })
+    }
+
+    /// Buy one strike and sell another of the same type and expiration
+    pub fn vertical_spread(
+        long: &'a OccSymbol,
+        short: &'a OccSymbol,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        same_type(&[long, short])?;
+        same_expiration(&[long, short])?;
+        if long.strike() == short.strike() {
+            return Err(Error::other("Vertical Spread Strikes Must Differ"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(long, OrderSide::Buy, 1),
+                OrderLeg::open(short, OrderSide::Sell, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Sell a put and call spread around the price, strikes ascend from long put to long call
+    pub fn iron_condor(
+        long_put: &'a OccSymbol,
+        short_put: &'a OccSymbol,
+        short_call: &'a OccSymbol,
+        long_call: &'a OccSymbol,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        is_type(&[long_put, short_put], OptionType::Put)?;
+        is_type(&[short_call, long_call], OptionType::Call)?;
+        same_expiration(&[long_put, short_put, short_call, long_call])?;
+        if !(long_put.strike() < short_put.strike()
+            && short_put.strike() <= short_call.strike()
+            && short_call.strike() < long_call.strike())
+        {
+            return Err(Error::other("Iron Condor Strikes Must Ascend"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(long_put, OrderSide::Buy, 1),
+                OrderLeg::open(short_put, OrderSide::Sell, 1),
+                OrderLeg::open(short_call, OrderSide::Sell, 1),
+                OrderLeg::open(long_call, OrderSide::Buy, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Buy or sell a call and put at the same strike and expiration
+    pub fn straddle(
+        call: &'a OccSymbol,
+        put: &'a OccSymbol,
+        side: OrderSide,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        is_type(&[call], OptionType::Call)?;
+        is_type(&[put], OptionType::Put)?;
+        same_expiration(&[call, put])?;
+        if call.strike() != put.strike() {
+            return Err(Error::other("Straddle Strikes Must Match"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(call, side.clone(), 1),
+                OrderLeg::open(put, side, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Buy or sell a put and a higher strike call of the same expiration
+    pub fn strangle(
+        put: &'a OccSymbol,
+        call: &'a OccSymbol,
+        side: OrderSide,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        is_type(&[put], OptionType::Put)?;
+        is_type(&[call], OptionType::Call)?;
+        same_expiration(&[put, call])?;
+        if put.strike() >= call.strike() {
+            return Err(Error::other("Strangle Put Strike Must Be Below The Call"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(put, side.clone(), 1),
+                OrderLeg::open(call, side, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Long butterfly, buy the wings and sell two of the evenly spaced middle strike
+    pub fn butterfly(
+        lower: &'a OccSymbol,
+        middle: &'a OccSymbol,
+        upper: &'a OccSymbol,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        same_type(&[lower, middle, upper])?;
+        same_expiration(&[lower, middle, upper])?;
+        let (low, mid, high) = (lower.strike(), middle.strike(), upper.strike());
+        if !(low < mid && mid < high) || (mid - low - (high - mid)).abs() > 1e-9 {
+            return Err(Error::other("Butterfly Strikes Must Be Evenly Spaced"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(lower, OrderSide::Buy, 1),
+                OrderLeg::open(middle, OrderSide::Sell, 2),
+                OrderLeg::open(upper, OrderSide::Buy, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Sell the near expiration and buy the far one at the same strike
+    pub fn calendar_spread(
+        near: &'a OccSymbol,
+        far: &'a OccSymbol,
+        qty: &'a str,
+    ) -> Result<Self, Error> {
+        same_type(&[near, far])?;
+        if near.strike() != far.strike() {
+            return Err(Error::other("Calendar Spread Strikes Must Match"));
+        }
+        if near.expiration() >= far.expiration() {
+            return Err(Error::other("Calendar Spread Near Leg Must Expire First"));
+        }
+        Self::new(
+            vec![
+                OrderLeg::open(near, OrderSide::Sell, 1),
+                OrderLeg::open(far, OrderSide::Buy, 1),
+            ],
+            qty,
+        )
+    }
+
+    /// Net price for one strategy unit, positive for a debit and negative for a credit
+    pub fn limit_price(mut self, limit_price: &'a str) -> Self {
+        self.r#type = OrderType::Limit.to_string();
+        self.limit_price = Some(limit_price);
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force.to_string();
+        self
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &'a str) -> Self {
+        self.client_order_id = Some(client_order_id);
+        self
+    }
+
+    pub fn legs(&self) -> &[OrderLeg<'a>] {
+        &self.legs
+    }
+
+    pub fn send(self, account_type: AccountType) -> Result<Order, ureq::Error> {
+        let url = match account_type {
+            AccountType::Live => "https://api.alpaca.markets/v2/orders",
+            AccountType::Paper => "https://paper-api.alpaca.markets/v2/orders",
+        };
+
+        let response = request("POST", url)
+            .set("Content-Type", "application/json")
+            .send_json(&self)?;
+
+        let order = response.into_json()?;
+        Ok(order)
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn is_type(symbols: &[&OccSymbol], option_type: OptionType) -> Result<(), Error> {
+    match symbols
+        .iter()
+        .find(|symbol| symbol.option_type() != option_type)
+    {
+        Some(symbol) => Err(Error::other(format!(
+            "{symbol} Is Not A {option_type} Contract"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn same_type(symbols: &[&OccSymbol]) -> Result<(), Error> {
+    is_type(symbols, symbols[0].option_type())
+}
+
+fn same_expiration(symbols: &[&OccSymbol]) -> Result<(), Error> {
+    if symbols
+        .iter()
+        .all(|symbol| symbol.expiration() == symbols[0].expiration())
+    {
+        Ok(())
+    } else {
+        Err(Error::other("Legs Must Share An Expiration"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(symbols: &[&str]) -> Vec<OccSymbol> {
+        symbols
+            .iter()
+            .map(|symbol| symbol.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_iron_condor_body() {
+        let legs = symbols(&[
+            "SPY241220P00560000",
+            "SPY241220P00570000",
+            "SPY241220C00600000",
+            "SPY241220C00610000",
+        ]);
+        let order = MultiLegOrderQuery::iron_condor(&legs[0], &legs[1], &legs[2], &legs[3], "2")
+            .unwrap()
+            .limit_price("-1.25");
+
+        let body = serde_json::to_value(&order).unwrap();
+        assert_eq!(body["order_class"], "mleg");
+        assert_eq!(body["type"], "limit");
+        assert_eq!(body["qty"], "2");
+        assert_eq!(body["limit_price"], "-1.25");
+        assert_eq!(body["legs"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            body["legs"][1],
+            serde_json::json!({
+                "symbol": "SPY241220P00570000",
+                "ratio_qty": "1",
+                "side": "sell",
+                "position_intent": "sell_to_open"
+            })
+        );
+    }
+
+    #[test]
+    fn test_butterfly_ratios() {
+        let legs = symbols(&[
+            "AAPL241220C00190000",
+            "AAPL241220C00200000",
+            "AAPL241220C00210000",
+        ]);
+        let order = MultiLegOrderQuery::butterfly(&legs[0], &legs[1], &legs[2], "1").unwrap();
+        let ratios: Vec<&str> = order
+            .legs()
+            .iter()
+            .map(|leg| leg.ratio_qty.as_str())
+            .collect();
+        assert_eq!(ratios, vec!["1", "2", "1"]);
+
+        assert!(MultiLegOrderQuery::butterfly(&legs[0], &legs[1], &legs[1], "1").is_err());
+    }
+
+    #[test]
+    fn test_strategy_validation() {
+        let legs = symbols(&[
+            "AAPL241220C00200000",
+            "AAPL241220P00200000",
+            "AAPL250117C00200000",
+            "AAPL241220C00210000",
+        ]);
+
+        assert!(MultiLegOrderQuery::straddle(&legs[0], &legs[1], OrderSide::Buy, "1").is_ok());
+        assert!(MultiLegOrderQuery::straddle(&legs[1], &legs[0], OrderSide::Buy, "1").is_err());
+        assert!(MultiLegOrderQuery::strangle(&legs[1], &legs[3], OrderSide::Sell, "1").is_ok());
+        assert!(MultiLegOrderQuery::vertical_spread(&legs[0], &legs[3], "1").is_ok());
+        assert!(MultiLegOrderQuery::vertical_spread(&legs[0], &legs[2], "1").is_err());
+        assert!(MultiLegOrderQuery::calendar_spread(&legs[0], &legs[2], "1").is_ok());
+        assert!(MultiLegOrderQuery::calendar_spread(&legs[2], &legs[0], "1").is_err());
+    }
+
+    #[test]
+    fn test_custom_leg_validation() {
+        let leg = |symbol: &'static str, ratio| OrderLeg::new(symbol, OrderSide::Buy, ratio);
+
+        assert!(MultiLegOrderQuery::new(vec![leg("A", 1)], "1").is_err());
+        assert!(MultiLegOrderQuery::new(vec![leg("A", 1), leg("A", 1)], "1").is_err());
+        assert!(MultiLegOrderQuery::new(vec![leg("A", 2), leg("B", 4)], "1").is_err());
+        assert!(MultiLegOrderQuery::new(vec![leg("A", 0), leg("B", 1)], "1").is_err());
+        assert!(MultiLegOrderQuery::new(vec![leg("A", 2), leg("B", 3)], "1").is_ok());
+    }
+}
@@ -0,0 +1,504 @@
+use super::CreateOrderQuery;
+use crate::trading::{account::Account, assets::Asset, positions::Position};
+use std::fmt;
+
+/// Reasons an order would be rejected, found before it is sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderValidationError {
+    /// Neither `qty` nor `notional` is set
+    MissingQuantity,
+    /// Both `qty` and `notional` are set
+    QtyAndNotional,
+    InvalidNumber {
+        field: &'static str,
+        value: String,
+    },
+    NotPositive {
+        field: &'static str,
+    },
+    MissingLimitPrice,
+    MissingStopPrice,
+    UnexpectedLimitPrice,
+    UnexpectedStopPrice,
+    /// Trailing stops need exactly one of `trail_price` or `trail_percent`
+    MissingTrail,
+    TrailPriceAndPercent,
+    UnexpectedTrail,
+    /// The asset given to validate against is for another symbol
+    AssetMismatch {
+        symbol: String,
+        asset: String,
+    },
+    NotTradable,
+    NotFractionable,
+    /// Fractional and notional orders must be day orders
+    FractionalTimeInForce,
+    NotShortable,
+    ShortingDisabled,
+    TradingBlocked,
+    /// Extended hours orders must be day limit orders
+    ExtendedHours,
+    MissingTakeProfit,
+    MissingStopLoss,
+    /// Take profit or stop loss set without a bracket, oco or oto order class
+    UnexpectedLegs,
+    /// Take profit, entry and stop loss prices are on the wrong sides of each other
+    BracketPriceOrder,
+}
+
+impl fmt::Display for OrderValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderValidationError::MissingQuantity => {
+                write!(f, "Either qty or notional is required")
+            }
+            OrderValidationError::QtyAndNotional => {
+                write!(f, "Only one of qty or notional can be set")
+            }
+            OrderValidationError::InvalidNumber { field, value } => {
+                write!(f, "Invalid {field}: {value}")
+            }
+            OrderValidationError::NotPositive { field } => write!(f, "{field} must be positive"),
+            OrderValidationError::MissingLimitPrice => {
+                write!(f, "Order type requires a limit price")
+            }
+            OrderValidationError::MissingStopPrice => write!(f, "Order type requires a stop price"),
+            OrderValidationError::UnexpectedLimitPrice => {
+                write!(f, "Order type does not take a limit price")
+            }
+            OrderValidationError::UnexpectedStopPrice => {
+                write!(f, "Order type does not take a stop price")
+            }
+            OrderValidationError::MissingTrail => {
+                write!(f, "Trailing stops require a trail price or trail percent")
+            }
+            OrderValidationError::TrailPriceAndPercent => {
+                write!(f, "Only one of trail price or trail percent can be set")
+            }
+            OrderValidationError::UnexpectedTrail => {
+                write!(f, "Only trailing stops take a trail price or percent")
+            }
+            OrderValidationError::AssetMismatch { symbol, asset } => {
+                write!(f, "Order is for {symbol} but the asset is {asset}")
+            }
+            OrderValidationError::NotTradable => write!(f, "Asset is not tradable"),
+            OrderValidationError::NotFractionable => write!(f, "Asset is not fractionable"),
+            OrderValidationError::FractionalTimeInForce => {
+                write!(f, "Fractional and notional orders must be day orders")
+            }
+            OrderValidationError::NotShortable => write!(f, "Asset is not shortable"),
+            OrderValidationError::ShortingDisabled => write!(f, "Account has shorting disabled"),
+            OrderValidationError::TradingBlocked => write!(f, "Account is blocked from trading"),
+            OrderValidationError::ExtendedHours => {
+                write!(f, "Extended hours orders must be day limit orders")
+            }
+            OrderValidationError::MissingTakeProfit => {
+                write!(f, "Order class requires a take profit")
+            }
+            OrderValidationError::MissingStopLoss => write!(f, "Order class requires a stop loss"),
+            OrderValidationError::UnexpectedLegs => {
+                write!(
+                    f,
+                    "Take profit and stop loss need a bracket, oco or oto order class"
+                )
+            }
+            OrderValidationError::BracketPriceOrder => {
+                write!(
+                    f,
+                    "Take profit and stop loss are on the wrong side of the entry"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderValidationError {}
+
+/// Asset and optional account state an order is validated against
+pub struct OrderContext<'a> {
+    asset: &'a Asset,
+    account: Option<&'a Account>,
+    position: Option<&'a Position>,
+}
+
+impl<'a> OrderContext<'a> {
+    pub fn new(asset: &'a Asset) -> Self {
+        Self {
+            asset,
+            account: None,
+            position: None,
+        }
+    }
+
+    pub fn account(mut self, account: &'a Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// The current position in the symbol, used to tell a sell from a short
+    pub fn position(mut self, position: &'a Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+fn parse(
+    field: &'static str,
+    value: Option<&str>,
+    errors: &mut Vec<OrderValidationError>,
+) -> Option<f64> {
+    let value = value?;
+    match value.parse::<f64>() {
+        Ok(number) if !number.is_finite() => {
+            errors.push(OrderValidationError::InvalidNumber {
+                field,
+                value: value.to_string(),
+            });
+            None
+        }
+        Ok(number) if number <= 0.0 => {
+            errors.push(OrderValidationError::NotPositive { field });
+            None
+        }
+        Ok(number) => Some(number),
+        Err(_) => {
+            errors.push(OrderValidationError::InvalidNumber {
+                field,
+                value: value.to_string(),
+            });
+            None
+        }
+    }
+}
+
+impl<'a> CreateOrderQuery<'a> {
+    /// Check the order against the rules the API enforces, returning every problem found.
+    /// Nothing is sent, so this can be called before `send`.
+    pub fn validate(&self, context: &OrderContext) -> Result<(), Vec<OrderValidationError>> {
+        let mut errors = Vec::new();
+
+        let qty = parse("qty", self.qty, &mut errors);
+        let notional = parse("notional", self.notional, &mut errors);
+        let limit_price = parse("limit_price", self.limit_price, &mut errors);
+        let stop_price = parse("stop_price", self.stop_price, &mut errors);
+        parse("trail_price", self.trail_price, &mut errors);
+        parse("trail_percent", self.trail_percent, &mut errors);
+
+        // Quantity
+        match (self.qty, self.notional) {
+            (None, None) => errors.push(OrderValidationError::MissingQuantity),
+            (Some(_), Some(_)) => errors.push(OrderValidationError::QtyAndNotional),
+            _ => {}
+        }
+
+        // Order type and price fields
+        let order_type = self.r#type.as_str();
+        let order_class = self.order_class.as_deref().unwrap_or_default();
+        let needs_limit = matches!(order_type, "limit" | "stop_limit");
+        let needs_stop = matches!(order_type, "stop" | "stop_limit");
+        // OCO orders are limit orders priced by their take profit leg, checked below
+        if order_class != "oco" {
+            match (needs_limit, self.limit_price.is_some()) {
+                (true, false) => errors.push(OrderValidationError::MissingLimitPrice),
+                (false, true) => errors.push(OrderValidationError::UnexpectedLimitPrice),
+                _ => {}
+            }
+        }
+        match (needs_stop, self.stop_price.is_some()) {
+            (true, false) => errors.push(OrderValidationError::MissingStopPrice),
+            (false, true) => errors.push(OrderValidationError::UnexpectedStopPrice),
+            _ => {}
+        }
+        match (
+            order_type == "trailing_stop",
+            self.trail_price.is_some(),
+            self.trail_percent.is_some(),
+        ) {
+            (true, false, false) => errors.push(OrderValidationError::MissingTrail),
+            (true, true, true) => errors.push(OrderValidationError::TrailPriceAndPercent),
+            (false, true, _) | (false, _, true) => {
+                errors.push(OrderValidationError::UnexpectedTrail)
+            }
+            _ => {}
+        }
+
+        // Asset
+        let asset = context.asset;
+        if asset.symbol != self.symbol {
+            errors.push(OrderValidationError::AssetMismatch {
+                symbol: self.symbol.to_string(),
+                asset: asset.symbol.clone(),
+            });
+        }
+        if !asset.tradable {
+            errors.push(OrderValidationError::NotTradable);
+        }
+        let fractional = notional.is_some() || qty.is_some_and(|qty| qty.fract() != 0.0);
+        if fractional {
+            if !asset.fractionable {
+                errors.push(OrderValidationError::NotFractionable);
+            }
+            if self.time_in_force != "day" {
+                errors.push(OrderValidationError::FractionalTimeInForce);
+            }
+        }
+
+        // Account
+        if self.side == "sell" {
+            let held = context
+                .position
+                .and_then(|position| position.qty.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let short = match qty {
+                Some(qty) => qty > held,
+                None => held <= 0.0,
+            };
+            if short {
+                if !asset.shortable {
+                    errors.push(OrderValidationError::NotShortable);
+                }
+                if context
+                    .account
+                    .is_some_and(|account| !account.shorting_enabled)
+                {
+                    errors.push(OrderValidationError::ShortingDisabled);
+                }
+            }
+        }
+        if context
+            .account
+            .is_some_and(|account| account.trading_blocked)
+        {
+            errors.push(OrderValidationError::TradingBlocked);
+        }
+
+        // Extended hours
+        if self.extend_hours && (order_type != "limit" || self.time_in_force != "day") {
+            errors.push(OrderValidationError::ExtendedHours);
+        }
+
+        // Order class legs
+        match order_class {
+            "bracket" | "oco" => {
+                if self.take_profit.is_none() {
+                    errors.push(OrderValidationError::MissingTakeProfit);
+                }
+                if self.stop_loss.is_none() {
+                    errors.push(OrderValidationError::MissingStopLoss);
+                }
+            }
+            "oto" => {
+                if self.take_profit.is_none() && self.stop_loss.is_none() {
+                    errors.push(OrderValidationError::MissingStopLoss);
+                }
+            }
+            _ => {
+                if self.take_profit.is_some() || self.stop_loss.is_some() {
+                    errors.push(OrderValidationError::UnexpectedLegs);
+                }
+            }
+        }
+
+        let take_profit = self.take_profit.as_ref().and_then(|take_profit| {
+            parse(
+                "take_profit.limit_price",
+                Some(take_profit.limit_price),
+                &mut errors,
+            )
+        });
+        let stop_loss = self.stop_loss.as_ref().and_then(|stop_loss| {
+            parse(
+                "stop_loss.stop_price",
+                Some(stop_loss.stop_price),
+                &mut errors,
+            )
+        });
+        if let (Some(take_profit), Some(stop_loss)) = (take_profit, stop_loss) {
+            // Exits of a long sit either side of the entry, a short mirrors them. An
+            // OCO order is the exit itself, so selling one closes a long.
+            let (entry, long) = if order_class == "oco" {
+                (None, self.side == "sell")
+            } else {
+                (limit_price.or(stop_price), self.side == "buy")
+            };
+            let (upper, lower) = if long {
+                (take_profit, stop_loss)
+            } else {
+                (stop_loss, take_profit)
+            };
+            let ordered = match entry {
+                Some(entry) => upper > entry && entry > lower,
+                None => upper > lower,
+            };
+            if !ordered {
+                errors.push(OrderValidationError::BracketPriceOrder);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::assets::test_asset;
+    use crate::trading::order::{
+        OrderClass, OrderSide, OrderType, StopLoss, TakeProfit, TimeInForce,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_valid_orders() {
+        let asset = test_asset(json!({}));
+        let context = OrderContext::new(&asset);
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Limit, TimeInForce::Day)
+                .qty("1.5")
+                .limit_price("100")
+                .extend_hours(true);
+        assert_eq!(order.validate(&context), Ok(()));
+
+        let order = CreateOrderQuery::new(
+            "AAPL",
+            OrderSide::Buy,
+            OrderType::Limit,
+            TimeInForce::GoodTilCanceled,
+        )
+        .qty("1")
+        .limit_price("100")
+        .order_class(OrderClass::Bracket)
+        .take_profit(TakeProfit::new("110"))
+        .stop_loss(StopLoss::new("95", "94"));
+        assert_eq!(order.validate(&context), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_price_fields() {
+        let asset = test_asset(json!({}));
+        let context = OrderContext::new(&asset);
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Limit, TimeInForce::Day)
+                .qty("1")
+                .notional("100");
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![
+                OrderValidationError::QtyAndNotional,
+                OrderValidationError::MissingLimitPrice
+            ])
+        );
+
+        let order = CreateOrderQuery::new(
+            "AAPL",
+            OrderSide::Buy,
+            OrderType::TrailingStop,
+            TimeInForce::Day,
+        )
+        .qty("1")
+        .stop_price("abc");
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![
+                OrderValidationError::InvalidNumber {
+                    field: "stop_price",
+                    value: "abc".to_string()
+                },
+                OrderValidationError::UnexpectedStopPrice,
+                OrderValidationError::MissingTrail,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_asset_rules() {
+        let asset = test_asset(json!({
+            "shortable": false,
+            "easy_to_borrow": false,
+            "fractionable": false
+        }));
+        let context = OrderContext::new(&asset);
+
+        let order = CreateOrderQuery::new(
+            "AAPL",
+            OrderSide::Sell,
+            OrderType::Market,
+            TimeInForce::GoodTilCanceled,
+        )
+        .qty("0.5")
+        .extend_hours(true);
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![
+                OrderValidationError::NotFractionable,
+                OrderValidationError::FractionalTimeInForce,
+                OrderValidationError::NotShortable,
+                OrderValidationError::ExtendedHours,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_bracket_prices() {
+        let asset = test_asset(json!({}));
+        let context = OrderContext::new(&asset);
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Limit, TimeInForce::Day)
+                .qty("1")
+                .limit_price("100")
+                .order_class(OrderClass::Bracket)
+                .take_profit(TakeProfit::new("95"))
+                .stop_loss(StopLoss::new("90", "89"));
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![OrderValidationError::BracketPriceOrder])
+        );
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Market, TimeInForce::Day)
+                .qty("1")
+                .take_profit(TakeProfit::new("110"));
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![OrderValidationError::UnexpectedLegs])
+        );
+    }
+
+    #[test]
+    fn test_validate_oco_prices() {
+        let asset = test_asset(json!({}));
+        let context = OrderContext::new(&asset);
+        let oco = |side, take_profit, stop_price| {
+            CreateOrderQuery::new("AAPL", side, OrderType::Limit, TimeInForce::GoodTilCanceled)
+                .qty("1")
+                .order_class(OrderClass::OneCancelsOther)
+                .take_profit(TakeProfit::new(take_profit))
+                .stop_loss(StopLoss::new(stop_price, stop_price))
+        };
+
+        // Closing a long takes profit above the stop, closing a short below it
+        assert_eq!(oco(OrderSide::Sell, "110", "95").validate(&context), Ok(()));
+        assert_eq!(oco(OrderSide::Buy, "90", "105").validate(&context), Ok(()));
+        assert_eq!(
+            oco(OrderSide::Sell, "95", "110").validate(&context),
+            Err(vec![OrderValidationError::BracketPriceOrder])
+        );
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Sell, OrderType::Limit, TimeInForce::Day)
+                .qty("1")
+                .order_class(OrderClass::OneCancelsOther)
+                .stop_loss(StopLoss::new("95", "94"));
+        assert_eq!(
+            order.validate(&context),
+            Err(vec![OrderValidationError::MissingTakeProfit])
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use super::{CreateOrderQuery, GetOrdersQuery, Order, OrderStatus};
+use crate::{trading::AccountType, TradeUpdate};
+use std::{
+    fmt, thread,
+    time::{Duration, Instant},
+};
+
+/// An increase in an order's filled quantity
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillUpdate {
+    pub order_id: String,
+    pub filled_qty: String,
+    pub filled_avg_price: Option<String>,
+    pub status: String,
+    pub at: Option<String>,
+}
+
+/// The last seen state of an order and every partial fill observed on the way
+#[derive(Debug, Clone)]
+pub struct OrderHistory {
+    pub order: Order,
+    pub fills: Vec<FillUpdate>,
+}
+
+fn filled_qty(order: &Order) -> f64 {
+    order
+        .filled_qty
+        .as_deref()
+        .and_then(|qty| qty.parse::<f64>().ok())
+        .unwrap_or_default()
+}
+
+impl OrderHistory {
+    pub fn new(order: Order) -> Self {
+        let mut history = Self {
+            fills: Vec::new(),
+            order: order.clone(),
+        };
+        if filled_qty(&order) > 0.0 {
+            history.record(&order, order.updated_at.clone());
+        }
+        history
+    }
+
+    fn record(&mut self, order: &Order, at: Option<String>) {
+        self.fills.push(FillUpdate {
+            order_id: order.id.clone(),
+            filled_qty: order.filled_qty.clone().unwrap_or_default(),
+            filled_avg_price: order.filled_avg_price.clone(),
+            status: order.status.clone(),
+            at,
+        });
+    }
+
+    /// Take a newer snapshot of the order, noting a fill if more has filled. A
+    /// replacement order starts its own filled quantity from zero.
+    pub fn observe(&mut self, order: Order, at: Option<String>) {
+        let previous = if order.id == self.order.id {
+            filled_qty(&self.order)
+        } else {
+            0.0
+        };
+        if filled_qty(&order) > previous {
+            self.record(&order, at.or(order.updated_at.clone()));
+        }
+        self.order = order;
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.order.status == OrderStatus::Filled.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub enum WaitError {
+    /// The timeout passed first, with the history seen so far
+    Timeout(Box<OrderHistory>),
+    /// The order finished without filling, e.g. it was canceled or expired
+    NotFilled(Box<OrderHistory>),
+    Request(Box<ureq::Error>),
+}
+
+impl From<ureq::Error> for WaitError {
+    fn from(error: ureq::Error) -> Self {
+        WaitError::Request(Box::new(error))
+    }
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitError::Timeout(history) => {
+                write!(f, "Timed out waiting for order {}", history.order.id)
+            }
+            WaitError::NotFilled(history) => write!(
+                f,
+                "Order {} ended as {} without filling",
+                history.order.id, history.order.status
+            ),
+            WaitError::Request(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for WaitError {}
+
+/// Polls orders until they fill or finish, backing off between requests
+pub struct OrderWaiter {
+    account_type: AccountType,
+    initial_interval: Duration,
+    max_interval: Duration,
+}
+
+impl OrderWaiter {
+    /// Polls after 250ms at first, doubling up to every 5s
+    pub fn new(account_type: AccountType) -> Self {
+        Self {
+            account_type,
+            initial_interval: Duration::from_millis(250),
+            max_interval: Duration::from_secs(5),
+        }
+    }
+
+    pub fn poll_interval(mut self, initial_interval: Duration, max_interval: Duration) -> Self {
+        self.initial_interval = initial_interval;
+        self.max_interval = max_interval.max(initial_interval);
+        self
+    }
+
+    /// Poll until `done` or the order can no longer fill, following replacements
+    fn poll<F: Fn(&Order) -> bool>(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+        done: F,
+    ) -> Result<OrderHistory, WaitError> {
+        let start = Instant::now();
+        let mut interval = self.initial_interval;
+        let mut order_id = order_id.to_string();
+        let mut history: Option<OrderHistory> = None;
+
+        loop {
+            let order = GetOrdersQuery::new(self.account_type).get_by_id(&order_id, false)?;
+            let history = match history.as_mut() {
+                Some(history) => {
+                    history.observe(order, None);
+                    history
+                }
+                None => history.insert(OrderHistory::new(order)),
+            };
+
+            match &history.order.replaced_by {
+                Some(replaced_by) if !history.order.is_open() => {
+                    order_id = replaced_by.clone();
+                    continue;
+                }
+                _ => {}
+            }
+            if done(&history.order) || !history.order.is_open() {
+                return Ok(history.clone());
+            }
+            if start.elapsed() + interval > timeout {
+                return Err(WaitError::Timeout(Box::new(history.clone())));
+            }
+
+            thread::sleep(interval);
+            interval = (interval * 2).min(self.max_interval);
+        }
+    }
+
+    /// Wait for the order to fill completely
+    pub fn wait_until_filled(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+    ) -> Result<OrderHistory, WaitError> {
+        let history = self.poll(order_id, timeout, |order| {
+            order.status == OrderStatus::Filled.to_string()
+        })?;
+        if history.is_filled() {
+            Ok(history)
+        } else {
+            Err(WaitError::NotFilled(Box::new(history)))
+        }
+    }
+
+    /// Wait for the order to fill, cancel, expire or be rejected
+    pub fn wait_until_terminal(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+    ) -> Result<OrderHistory, WaitError> {
+        self.poll(order_id, timeout, |order| !order.is_open())
+    }
+
+    /// Submit an order and wait for it to reach a terminal status
+    pub fn submit_and_wait(
+        &self,
+        order: CreateOrderQuery,
+        timeout: Duration,
+    ) -> Result<OrderHistory, WaitError> {
+        let order = order.send(self.account_type)?;
+        if !order.is_open() {
+            return Ok(OrderHistory::new(order));
+        }
+        self.wait_until_terminal(&order.id, timeout)
+    }
+}
+
+/// Follow an order through a stream of trade updates until it reaches a terminal
+/// status, returning `None` if the stream ends first
+pub fn wait_on_trade_updates<I: IntoIterator<Item = TradeUpdate>>(
+    order_id: &str,
+    updates: I,
+) -> Option<OrderHistory> {
+    let mut order_id = order_id.to_string();
+    let mut history: Option<OrderHistory> = None;
+
+    for update in updates {
+        if update.order.id != order_id {
+            continue;
+        }
+        let history = match history.as_mut() {
+            Some(history) => {
+                history.observe(update.order, update.timestamp);
+                history
+            }
+            None => history.insert(OrderHistory::new(update.order)),
+        };
+
+        match &history.order.replaced_by {
+            Some(replaced_by) if !history.order.is_open() => order_id = replaced_by.clone(),
+            _ if !history.order.is_open() => return Some(history.clone()),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::test_order;
+    use serde_json::json;
+
+    fn order(id: &str, status: &str, filled_qty: &str) -> Order {
+        test_order(id, status, json!({ "filled_qty": filled_qty }))
+    }
+
+    fn update(order: Order) -> TradeUpdate {
+        TradeUpdate {
+            event: order.status.clone(),
+            order,
+            timestamp: None,
+            price: None,
+            qty: None,
+            position_qty: None,
+            execution_id: None,
+        }
+    }
+
+    #[test]
+    fn test_history_records_partial_fills() {
+        let mut history = OrderHistory::new(order("1", "new", "0"));
+        history.observe(order("1", "partially_filled", "4"), None);
+        history.observe(order("1", "partially_filled", "4"), None);
+        history.observe(order("1", "filled", "10"), None);
+
+        let fills: Vec<&str> = history
+            .fills
+            .iter()
+            .map(|fill| fill.filled_qty.as_str())
+            .collect();
+        assert_eq!(fills, vec!["4", "10"]);
+        assert!(history.is_filled());
+    }
+
+    #[test]
+    fn test_wait_on_trade_updates() {
+        let mut replaced = order("1", "replaced", "2");
+        replaced.replaced_by = Some("2".to_string());
+        let updates = vec![
+            update(order("1", "new", "0")),
+            update(order("1", "partially_filled", "2")),
+            update(order("9", "filled", "5")),
+            update(replaced),
+            update(order("2", "partially_filled", "3")),
+            update(order("2", "filled", "8")),
+        ];
+
+        let history = wait_on_trade_updates("1", updates).unwrap();
+        assert_eq!(history.order.id, "2");
+        assert!(history.is_filled());
+        assert_eq!(history.fills.len(), 3);
+
+        assert!(wait_on_trade_updates("1", vec![update(order("1", "new", "0"))]).is_none());
+    }
+}
@@ -0,0 +1,458 @@
+//! Pattern day trader guard. Accounts under the equity threshold are flagged as pattern
+//! day traders after four day trades in five business days, so closing orders that
+//! would be the fourth can be warned about or blocked before they are sent.
+
+use super::{
+    account::{get_account, Account},
+    activities::{ActivitiesQuery, TradeActivity},
+    order::{Order, OrderSide},
+    positions::{Position, PositionsQuery},
+    AccountType,
+};
+use crate::time::{new_york_offset, parse_timestamp};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// Equity an account needs to day trade without PDT restrictions
+pub const PDT_EQUITY_THRESHOLD: f64 = 25_000.0;
+
+/// Day trades within five business days that flag an account
+pub const PDT_DAY_TRADE_LIMIT: i32 = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SymbolDay {
+    /// Signed position, positive for long
+    position: f64,
+    /// Shares opened today that are still open, in the direction of the position
+    opened: f64,
+    /// Whether the current run of closing fills has already counted as a day trade
+    counted: bool,
+}
+
+impl SymbolDay {
+    /// Apply a signed fill, returning whether it completes a day trade
+    fn fill(&mut self, delta: f64) -> bool {
+        let position = self.position;
+        let closing = if position * delta < 0.0 {
+            delta.abs().min(position.abs())
+        } else {
+            0.0
+        };
+        let opening = delta.abs() - closing;
+
+        // Shares opened today are assumed to close first, so the guard never
+        // misses a day trade
+        let day_trade = closing > 0.0 && self.opened > 0.0 && !self.counted;
+        if closing > 0.0 {
+            self.opened = (self.opened - closing).max(0.0);
+            if day_trade {
+                self.counted = true;
+            }
+        }
+        if opening > 0.0 {
+            let flipped = position * (position + delta) < 0.0;
+            self.opened = if flipped {
+                opening
+            } else {
+                self.opened + opening
+            };
+            self.counted = false;
+        }
+
+        self.position = position + delta;
+        day_trade
+    }
+}
+
+/// Same-day round trips, rebuilt from today's fills
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayTradeTracker {
+    symbols: HashMap<String, SymbolDay>,
+    day_trades: i32,
+}
+
+fn signed_qty(side: &str, qty: f64) -> f64 {
+    if side.starts_with("sell") {
+        -qty
+    } else {
+        qty
+    }
+}
+
+fn new_york_date(timestamp: DateTime<Utc>) -> NaiveDate {
+    timestamp
+        .with_timezone(&new_york_offset(timestamp.date_naive()))
+        .date_naive()
+}
+
+impl DayTradeTracker {
+    /// Track from the positions held at the start of the day
+    pub fn new(positions: HashMap<String, f64>) -> Self {
+        Self {
+            symbols: positions
+                .into_iter()
+                .map(|(symbol, position)| {
+                    let day = SymbolDay {
+                        position,
+                        ..Default::default()
+                    };
+                    (symbol, day)
+                })
+                .collect(),
+            day_trades: 0,
+        }
+    }
+
+    /// Replay signed fills on top of current positions, unwinding them first to
+    /// find the positions held at the start of the day
+    fn replay(
+        positions: &[Position],
+        mut fills: Vec<(Option<DateTime<Utc>>, String, f64)>,
+    ) -> Self {
+        let mut start: HashMap<String, f64> = positions
+            .iter()
+            .map(|position| {
+                let qty = position.qty.parse::<f64>().unwrap_or_default();
+                (position.symbol.clone(), qty)
+            })
+            .collect();
+        for (_, symbol, delta) in &fills {
+            *start.entry(symbol.clone()).or_default() -= delta;
+        }
+
+        fills.sort_by_key(|(time, _, _)| *time);
+        let mut tracker = Self::new(start);
+        for (_, symbol, delta) in fills {
+            tracker.record(&symbol, delta);
+        }
+        tracker
+    }
+
+    /// Rebuild today's round trips from today's `FILL` activities
+    pub fn from_fills(positions: &[Position], fills: &[TradeActivity]) -> Self {
+        let fills = fills
+            .iter()
+            .filter(|fill| fill.activity_type.as_deref().unwrap_or("FILL") == "FILL")
+            .filter_map(|fill| {
+                let qty = fill.qty.as_deref()?.parse::<f64>().ok()?;
+                Some((
+                    fill.transaction_time
+                        .as_deref()
+                        .and_then(|time| parse_timestamp(time).ok()),
+                    fill.symbol.clone()?,
+                    signed_qty(fill.side.as_deref()?, qty),
+                ))
+            })
+            .collect();
+        Self::replay(positions, fills)
+    }
+
+    /// Rebuild today's round trips from orders filled on `today`, a New York date
+    pub fn from_orders(positions: &[Position], orders: &[Order], today: NaiveDate) -> Self {
+        let fills = orders
+            .iter()
+            .filter_map(|order| {
+                let filled_at = parse_timestamp(order.filled_at.as_deref()?).ok()?;
+                if new_york_date(filled_at) != today {
+                    return None;
+                }
+                let qty = order.filled_qty.as_deref()?.parse::<f64>().ok()?;
+                let delta = signed_qty(&order.side, qty);
+                Some((Some(filled_at), order.symbol.clone(), delta))
+            })
+            .collect();
+        Self::replay(positions, fills)
+    }
+
+    fn record(&mut self, symbol: &str, delta: f64) {
+        if self
+            .symbols
+            .entry(symbol.to_string())
+            .or_default()
+            .fill(delta)
+        {
+            self.day_trades += 1;
+        }
+    }
+
+    /// Record a fill made after the tracker was built
+    pub fn record_fill(&mut self, symbol: &str, side: OrderSide, qty: f64) {
+        self.record(symbol, signed_qty(&side.to_string(), qty));
+    }
+
+    /// Whether filling this order would complete a day trade
+    pub fn would_day_trade(&self, symbol: &str, side: OrderSide, qty: f64) -> bool {
+        let mut day = self.symbols.get(symbol).copied().unwrap_or_default();
+        day.fill(signed_qty(&side.to_string(), qty))
+    }
+
+    /// Day trades completed today
+    pub fn day_trades(&self) -> i32 {
+        self.day_trades
+    }
+}
+
+/// Why a closing order was flagged
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdtReason {
+    /// The day trade would be this many within five business days
+    WouldFlagAccount { day_trades: i32 },
+    /// The account is already a pattern day trader below the equity threshold
+    RestrictedAccount,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdtVerdict {
+    Allow,
+    Warn(PdtReason),
+    Block(PdtReason),
+}
+
+pub struct PdtGuard {
+    tracker: DayTradeTracker,
+    /// Equity at the previous close, which the broker checks against the threshold
+    last_equity: f64,
+    daytrade_count: i32,
+    pattern_day_trader: bool,
+    block: bool,
+}
+
+impl PdtGuard {
+    /// Blocks flagged orders by default, see `warn_only`
+    pub fn new(account: &Account, tracker: DayTradeTracker) -> Self {
+        Self {
+            tracker,
+            last_equity: account.last_equity.parse::<f64>().unwrap_or_default(),
+            daytrade_count: account.daytrade_count,
+            pattern_day_trader: account.pattern_day_trader,
+            block: true,
+        }
+    }
+
+    /// Build a guard from the live account, positions and today's fills
+    pub fn fetch(account_type: AccountType) -> Result<Self, ureq::Error> {
+        let account = get_account(account_type)?;
+        let positions = PositionsQuery::new(account_type).get_all_open_positions()?;
+        let now = Utc::now();
+        let today = now
+            .with_timezone(&new_york_offset(now.date_naive()))
+            .date_naive()
+            .to_string();
+        let fills = ActivitiesQuery::new(account_type)
+            .activity_types(vec!["FILL"])
+            .date(&today)
+            .send()?;
+
+        Ok(Self::new(
+            &account,
+            DayTradeTracker::from_fills(&positions, &fills),
+        ))
+    }
+
+    /// Warn about flagged orders instead of blocking them
+    pub fn warn_only(mut self) -> Self {
+        self.block = false;
+        self
+    }
+
+    pub fn tracker(&self) -> &DayTradeTracker {
+        &self.tracker
+    }
+
+    /// Record a fill so later checks see it
+    pub fn record_fill(&mut self, symbol: &str, side: OrderSide, qty: f64) {
+        if self.tracker.would_day_trade(symbol, side.clone(), qty) {
+            self.daytrade_count += 1;
+        }
+        self.tracker.record_fill(symbol, side, qty);
+    }
+
+    pub fn check(&self, symbol: &str, side: OrderSide, qty: f64) -> PdtVerdict {
+        if self.last_equity >= PDT_EQUITY_THRESHOLD
+            || !self.tracker.would_day_trade(symbol, side, qty)
+        {
+            return PdtVerdict::Allow;
+        }
+
+        let day_trades = self.daytrade_count + 1;
+        let reason = if self.pattern_day_trader {
+            PdtReason::RestrictedAccount
+        } else if day_trades >= PDT_DAY_TRADE_LIMIT {
+            PdtReason::WouldFlagAccount { day_trades }
+        } else {
+            return PdtVerdict::Allow;
+        };
+
+        if self.block {
+            PdtVerdict::Block(reason)
+        } else {
+            PdtVerdict::Warn(reason)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> DayTradeTracker {
+        let mut start = HashMap::new();
+        start.insert("MSFT".to_string(), 10.0);
+        DayTradeTracker::new(start)
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut tracker = tracker();
+
+        // Selling an overnight position is not a day trade
+        assert!(!tracker.would_day_trade("MSFT", OrderSide::Sell, 5.0));
+
+        tracker.record_fill("AAPL", OrderSide::Buy, 10.0);
+        assert!(tracker.would_day_trade("AAPL", OrderSide::Sell, 5.0));
+        assert!(!tracker.would_day_trade("AAPL", OrderSide::Buy, 5.0));
+
+        // Several closing fills in a row count once
+        tracker.record_fill("AAPL", OrderSide::Sell, 5.0);
+        tracker.record_fill("AAPL", OrderSide::Sell, 5.0);
+        assert_eq!(tracker.day_trades(), 1);
+
+        // Reopening starts a new round trip, as does a same day short
+        tracker.record_fill("AAPL", OrderSide::Buy, 1.0);
+        tracker.record_fill("AAPL", OrderSide::Sell, 3.0);
+        assert_eq!(tracker.day_trades(), 2);
+        assert!(tracker.would_day_trade("AAPL", OrderSide::Buy, 2.0));
+    }
+
+    #[test]
+    fn test_tracker_from_fills() {
+        let fills: Vec<TradeActivity> = serde_json::from_value(serde_json::json!([
+            {
+                "activity_type": "FILL",
+                "id": "2",
+                "qty": "10",
+                "side": "sell",
+                "symbol": "AAPL",
+                "transaction_time": "2024-07-01T15:00:00Z"
+            },
+            {
+                "activity_type": "FILL",
+                "id": "1",
+                "qty": "10",
+                "side": "buy",
+                "symbol": "AAPL",
+                "transaction_time": "2024-07-01T14:00:00Z"
+            }
+        ]))
+        .unwrap();
+
+        let tracker = DayTradeTracker::from_fills(&[], &fills);
+        assert_eq!(tracker.day_trades(), 1);
+        assert!(!tracker.would_day_trade("AAPL", OrderSide::Sell, 1.0));
+    }
+
+    #[test]
+    fn test_replay_sorts_fills_by_time() {
+        let positions: Vec<Position> = serde_json::from_value(serde_json::json!([{
+            "asset_id": "1", "symbol": "AAPL", "exchange": "NASDAQ", "asset_class": "us_equity",
+            "avg_entry_price": "200", "qty": "10", "qty_available": "10", "side": "long",
+            "market_value": "2000", "cost_basis": "2000", "unrealized_pl": "0",
+            "unrealized_plpc": "0", "unrealized_intraday_pl": "0",
+            "unrealized_intraday_plpc": "0", "current_price": "200", "lastday_price": "200",
+            "change_today": "0"
+        }]))
+        .unwrap();
+        // The overnight position is sold first, the buy half a second later sorts
+        // first as text
+        let fills: Vec<TradeActivity> = serde_json::from_value(serde_json::json!([
+            {
+                "activity_type": "FILL",
+                "id": "2",
+                "qty": "10",
+                "side": "buy",
+                "symbol": "AAPL",
+                "transaction_time": "2024-07-01T14:00:00.5Z"
+            },
+            {
+                "activity_type": "FILL",
+                "id": "1",
+                "qty": "10",
+                "side": "sell",
+                "symbol": "AAPL",
+                "transaction_time": "2024-07-01T14:00:00Z"
+            }
+        ]))
+        .unwrap();
+
+        let tracker = DayTradeTracker::from_fills(&positions, &fills);
+        assert_eq!(tracker.day_trades(), 0);
+        assert!(tracker.would_day_trade("AAPL", OrderSide::Sell, 10.0));
+    }
+
+    #[test]
+    fn test_guard_uses_last_equity() {
+        // Above the threshold now but not at the previous close
+        let account: Account = serde_json::from_value(serde_json::json!({
+            "id": "1", "admin_configurations": {}, "user_configurations": null,
+            "account_number": "PA1", "status": "ACTIVE", "crypto_status": "ACTIVE",
+            "currency": "USD", "buying_power": "0", "regt_buying_power": "0",
+            "daytrading_buying_power": "0", "options_buying_power": "0",
+            "effective_buying_power": "0", "non_marginable_buying_power": "0",
+            "bod_dtbp": "0", "cash": "0", "accrued_fees": "0", "portfolio_value": "30000",
+            "pattern_day_trader": false, "trading_blocked": false, "transfers_blocked": false,
+            "account_blocked": false, "created_at": "2024-01-01T00:00:00Z",
+            "trade_suspended_by_user": false, "multiplier": "1", "shorting_enabled": false,
+            "equity": "30000", "last_equity": "20000", "long_market_value": "30000",
+            "short_market_value": "0", "initial_margin": "0", "maintenance_margin": "0",
+            "last_maintenance_margin": "0", "sma": "0", "daytrade_count": 3,
+            "balance_asof": "2024-07-01", "crypto_tier": 0, "options_trading_level": 0,
+            "intraday_adjustments": "0", "pending_reg_taf_fees": "0"
+        }))
+        .unwrap();
+        let mut tracker = tracker();
+        tracker.record_fill("AAPL", OrderSide::Buy, 10.0);
+
+        let guard = PdtGuard::new(&account, tracker);
+        assert_eq!(
+            guard.check("AAPL", OrderSide::Sell, 10.0),
+            PdtVerdict::Block(PdtReason::WouldFlagAccount { day_trades: 4 })
+        );
+    }
+
+    #[test]
+    fn test_guard_verdicts() {
+        let mut tracker = tracker();
+        tracker.record_fill("AAPL", OrderSide::Buy, 10.0);
+        let guard = PdtGuard {
+            tracker,
+            last_equity: 10_000.0,
+            daytrade_count: 3,
+            pattern_day_trader: false,
+            block: true,
+        };
+
+        assert_eq!(
+            guard.check("AAPL", OrderSide::Sell, 10.0),
+            PdtVerdict::Block(PdtReason::WouldFlagAccount { day_trades: 4 })
+        );
+        assert_eq!(
+            guard.check("MSFT", OrderSide::Sell, 10.0),
+            PdtVerdict::Allow
+        );
+
+        let guard = guard.warn_only();
+        assert_eq!(
+            guard.check("AAPL", OrderSide::Sell, 10.0),
+            PdtVerdict::Warn(PdtReason::WouldFlagAccount { day_trades: 4 })
+        );
+
+        let guard = PdtGuard {
+            last_equity: 30_000.0,
+            ..guard
+        };
+        assert_eq!(
+            guard.check("AAPL", OrderSide::Sell, 10.0),
+            PdtVerdict::Allow
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use super::AccountType;
 use crate::{request, TimeFrame};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct PortfolioHistory {
     pub timestamp: Vec<i64>,
     pub equity: Vec<f64>,
@@ -99,7 +99,7 @@ impl<'a> PortfolioHistoryQuery<'a> {
             query.push_str(&format!("&period={}", period));
         }
         if let Some(timeframe) = self.timeframe {
-            query.push_str(&format!("&timeframe={}", timeframe.to_string()));
+            query.push_str(&format!("&timeframe={}", timeframe.short_form()));
         }
         if let Some(intraday_reporting) = self.intraday_reporting {
             query.push_str(&format!("&intraday_reporting={}", intraday_reporting));
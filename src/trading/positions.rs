@@ -1,18 +1,18 @@
 use super::{order::Order, AccountType};
 use crate::request;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub type AllPositions = Vec<Position>;
 pub type AllClosedPositions = Vec<ClosedPosition>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ClosedPosition {
     pub symbol: String,
     pub status: i32,
     pub body: Order,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Position {
     pub asset_id: String,
     pub symbol: String,
@@ -105,6 +105,34 @@ impl<'a> PositionsQuery<'a> {
 
         Ok(position)
     }
+
+    /// Exercise a held option contract, by OCC symbol or contract id
+    ///
+    /// The exercise settles as OPEXC and OPASN activities rather than an order.
+    pub fn exercise<S: AsRef<str> + ?Sized>(
+        &self,
+        symbol_or_contract_id: &S,
+    ) -> Result<(), ureq::Error> {
+        let route = format!("{}/{}/exercise", self.url, symbol_or_contract_id.as_ref());
+        request("POST", &route).call()?;
+
+        Ok(())
+    }
+
+    /// Instruct that a held in the money option contract must not be auto exercised at expiry
+    pub fn do_not_exercise<S: AsRef<str> + ?Sized>(
+        &self,
+        symbol_or_contract_id: &S,
+    ) -> Result<(), ureq::Error> {
+        let route = format!(
+            "{}/{}/do-not-exercise",
+            self.url,
+            symbol_or_contract_id.as_ref()
+        );
+        request("POST", &route).call()?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,526 @@
+//! Rebalance a portfolio to target weights. A plan can be computed and printed as a
+//! dry run, or executed with sells sent before buys so their proceeds fund the buys.
+
+use super::{
+    account::get_account,
+    assets::AssetsQuery,
+    order::{CreateOrderQuery, Order, OrderSide, OrderType, OrderWaiter, TimeInForce, WaitError},
+    positions::PositionsQuery,
+    AccountType,
+};
+use crate::market_data::stocks::{LatestTradesQuery, SnapshotsQuery};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    io::{Error, ErrorKind},
+    time::Duration,
+};
+
+/// A symbol's current position and price, held or targeted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub symbol: String,
+    pub qty: f64,
+    pub price: f64,
+    pub fractionable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebalanceOrder {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub qty: f64,
+    pub price: f64,
+    pub current_qty: f64,
+    pub target_weight: f64,
+    pub fractionable: bool,
+}
+
+impl RebalanceOrder {
+    pub fn notional(&self) -> f64 {
+        self.qty * self.price
+    }
+
+    /// A market day order for the planned quantity
+    pub fn send(&self, account_type: AccountType) -> Result<Order, ureq::Error> {
+        CreateOrderQuery::new(
+            &self.symbol,
+            self.side.clone(),
+            OrderType::Market,
+            TimeInForce::Day,
+        )
+        .qty(&format_qty(self.qty))
+        .send(account_type)
+    }
+}
+
+/// Orders needed to reach the target weights, sells first
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebalancePlan {
+    pub equity: f64,
+    pub cash: f64,
+    /// Cash left once every order fills at the planned prices
+    pub cash_after: f64,
+    pub orders: Vec<RebalanceOrder>,
+}
+
+impl RebalancePlan {
+    pub fn sells(&self) -> impl Iterator<Item = &RebalanceOrder> {
+        self.orders.iter().filter(|o| o.side == OrderSide::Sell)
+    }
+
+    pub fn buys(&self) -> impl Iterator<Item = &RebalanceOrder> {
+        self.orders.iter().filter(|o| o.side == OrderSide::Buy)
+    }
+}
+
+impl fmt::Display for RebalancePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Equity {:.2}, cash {:.2} -> {:.2}",
+            self.equity, self.cash, self.cash_after
+        )?;
+        for order in &self.orders {
+            writeln!(
+                f,
+                "{:<4} {:>14} {:<8} @ {:>10.2} = {:>12.2} (held {}, target {:.2}%)",
+                order.side.to_string(),
+                format_qty(order.qty),
+                order.symbol,
+                order.price,
+                order.notional(),
+                format_qty(order.current_qty),
+                order.target_weight * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A request failed part way through a rebalance, `orders` holds the orders already
+/// sent so they can be followed up
+#[derive(Debug)]
+pub struct RebalanceError {
+    pub orders: Vec<Order>,
+    pub error: Box<ureq::Error>,
+}
+
+impl RebalanceError {
+    fn new(orders: Vec<Order>, error: ureq::Error) -> Self {
+        Self {
+            orders,
+            error: Box::new(error),
+        }
+    }
+}
+
+impl From<ureq::Error> for RebalanceError {
+    fn from(error: ureq::Error) -> Self {
+        Self::new(Vec::new(), error)
+    }
+}
+
+impl fmt::Display for RebalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rebalance stopped after sending {} orders: {}",
+            self.orders.len(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for RebalanceError {}
+
+/// Fractional quantities go to nine decimal places, the most orders accept
+fn format_qty(qty: f64) -> String {
+    ((qty * 1e9).round() / 1e9).to_string()
+}
+
+/// Round a quantity down to what can be ordered
+fn tradable_qty(qty: f64, fractionable: bool) -> f64 {
+    if fractionable {
+        (qty * 1e9).floor() / 1e9
+    } else {
+        qty.floor()
+    }
+}
+
+pub struct Rebalancer {
+    targets: HashMap<String, f64>,
+    cash_buffer: f64,
+    min_notional: f64,
+    sell_timeout: Duration,
+}
+
+impl Rebalancer {
+    /// Target weights by symbol, as fractions of equity. Held symbols without a
+    /// target are sold.
+    pub fn new(targets: HashMap<String, f64>) -> Self {
+        Self {
+            targets,
+            cash_buffer: 0.0,
+            min_notional: 1.0,
+            sell_timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Fraction of equity to keep in cash
+    pub fn cash_buffer(mut self, cash_buffer: f64) -> Self {
+        self.cash_buffer = cash_buffer;
+        self
+    }
+
+    /// Skip trades smaller than this, other than closing a position entirely
+    pub fn min_notional(mut self, min_notional: f64) -> Self {
+        self.min_notional = min_notional;
+        self
+    }
+
+    /// How long to wait for sells to fill before buying
+    pub fn sell_timeout(mut self, sell_timeout: Duration) -> Self {
+        self.sell_timeout = sell_timeout;
+        self
+    }
+
+    /// Work out the orders from cash and holdings, which must include a price for
+    /// every targeted symbol
+    pub fn plan(&self, cash: f64, holdings: &[Holding]) -> Result<RebalancePlan, Error> {
+        let total_weight: f64 = self.targets.values().sum();
+        if self.targets.values().any(|w| *w < 0.0) || !(0.0..1.0).contains(&self.cash_buffer) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Weights And Cash Buffer Must Not Be Negative",
+            ));
+        }
+        if total_weight + self.cash_buffer > 1.0 + 1e-9 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Weights And Cash Buffer Exceed 100%",
+            ));
+        }
+        if let Some(symbol) = self.targets.keys().find(|symbol| {
+            !holdings
+                .iter()
+                .any(|h| &h.symbol == *symbol && h.price > 0.0)
+        }) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("No Price For {symbol}"),
+            ));
+        }
+
+        let equity = cash + holdings.iter().map(|h| h.qty * h.price).sum::<f64>();
+        let investable = equity * (1.0 - self.cash_buffer);
+        let mut sells = Vec::new();
+        let mut buys = Vec::new();
+
+        for holding in holdings.iter().filter(|h| h.price > 0.0) {
+            let target_weight = self.targets.get(&holding.symbol).copied().unwrap_or(0.0);
+            let target_qty = target_weight * investable / holding.price;
+            let order = |side, qty| RebalanceOrder {
+                symbol: holding.symbol.clone(),
+                side,
+                qty,
+                price: holding.price,
+                current_qty: holding.qty,
+                target_weight,
+                fractionable: holding.fractionable,
+            };
+
+            if target_qty < holding.qty {
+                // Sells never open a short, and an untargeted position is closed in full
+                let qty = if target_weight == 0.0 && holding.qty > 0.0 {
+                    holding.qty
+                } else {
+                    tradable_qty(holding.qty - target_qty.max(0.0), holding.fractionable)
+                        .min(holding.qty.max(0.0))
+                };
+                let closing = qty == holding.qty;
+                if qty > 0.0 && (closing || qty * holding.price >= self.min_notional) {
+                    sells.push(order(OrderSide::Sell, qty));
+                }
+            } else {
+                let qty = target_qty - holding.qty;
+                if qty * holding.price >= self.min_notional {
+                    buys.push(order(OrderSide::Buy, qty));
+                }
+            }
+        }
+
+        // Buys share out whatever cash the sells and buffer leave
+        let proceeds: f64 = sells.iter().map(RebalanceOrder::notional).sum();
+        let available = (cash + proceeds - equity * self.cash_buffer).max(0.0);
+        let wanted: f64 = buys.iter().map(RebalanceOrder::notional).sum();
+        let scale = if wanted > available {
+            available / wanted
+        } else {
+            1.0
+        };
+        buys = self.scale_buys(buys.iter(), scale);
+
+        sells.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        buys.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let spent: f64 = buys.iter().map(RebalanceOrder::notional).sum();
+        sells.extend(buys);
+
+        Ok(RebalancePlan {
+            equity,
+            cash,
+            cash_after: cash + proceeds - spent,
+            orders: sells,
+        })
+    }
+
+    /// Plan against the live account, its positions and the latest trade prices
+    pub fn fetch_plan(&self, account_type: AccountType) -> Result<RebalancePlan, ureq::Error> {
+        let account = get_account(account_type)?;
+        let cash = account.cash.parse::<f64>().unwrap_or_default();
+        let positions = PositionsQuery::new(account_type).get_all_open_positions()?;
+
+        let mut quantities: HashMap<String, f64> = self
+            .targets
+            .keys()
+            .map(|symbol| (symbol.clone(), 0.0))
+            .collect();
+        for position in &positions {
+            quantities.insert(
+                position.symbol.clone(),
+                position.qty.parse::<f64>().unwrap_or_default(),
+            );
+        }
+        let symbols: BTreeSet<&str> = quantities.keys().map(String::as_str).collect();
+
+        let mut prices: HashMap<String, f64> =
+            LatestTradesQuery::new(symbols.iter().copied().collect())
+                .send()?
+                .into_iter()
+                .map(|(symbol, trade)| (symbol, trade.p))
+                .collect();
+        let missing: Vec<&str> = symbols
+            .iter()
+            .copied()
+            .filter(|symbol| !prices.contains_key(*symbol))
+            .collect();
+        if !missing.is_empty() {
+            for (symbol, snapshot) in SnapshotsQuery::new(missing).send()? {
+                let price = snapshot
+                    .latest_trade
+                    .map(|trade| trade.p)
+                    .or(snapshot.prev_daily_bar.map(|bar| bar.c as f64));
+                if let Some(price) = price {
+                    prices.insert(symbol, price);
+                }
+            }
+        }
+
+        let mut holdings = Vec::new();
+        for (symbol, qty) in quantities {
+            let fractionable = AssetsQuery::new(account_type)
+                .get_by_symbol(&symbol)?
+                .fractionable;
+            holdings.push(Holding {
+                price: prices.get(&symbol).copied().unwrap_or_default(),
+                symbol,
+                qty,
+                fractionable,
+            });
+        }
+
+        Ok(self.plan(cash, &holdings)?)
+    }
+
+    /// Scale buys down to quantities that can be ordered, dropping any left too small
+    fn scale_buys<'a>(
+        &self,
+        buys: impl Iterator<Item = &'a RebalanceOrder>,
+        scale: f64,
+    ) -> Vec<RebalanceOrder> {
+        buys.filter_map(|order| {
+            let mut order = order.clone();
+            order.qty = tradable_qty(order.qty * scale, order.fractionable);
+            (order.qty > 0.0 && order.notional() >= self.min_notional).then_some(order)
+        })
+        .collect()
+    }
+
+    /// A plan's buys, scaled down to fit when its sells brought in less than planned
+    fn funded_buys(&self, plan: &RebalancePlan, proceeds: f64) -> Vec<RebalanceOrder> {
+        let available = (plan.cash + proceeds - plan.equity * self.cash_buffer).max(0.0);
+        let wanted: f64 = plan.buys().map(RebalanceOrder::notional).sum();
+        let scale = if wanted > available {
+            available / wanted
+        } else {
+            1.0
+        };
+        self.scale_buys(plan.buys(), scale)
+    }
+
+    /// Send a plan's sells and wait for them to finish, then send its buys sized to
+    /// the proceeds the sells actually brought in. A sell still open after
+    /// `sell_timeout` only funds what it has filled.
+    pub fn execute(
+        &self,
+        plan: &RebalancePlan,
+        account_type: AccountType,
+    ) -> Result<Vec<Order>, RebalanceError> {
+        let waiter = OrderWaiter::new(account_type);
+        let mut orders = Vec::new();
+
+        for order in plan.sells() {
+            match order.send(account_type) {
+                Ok(sent) => orders.push(sent),
+                Err(error) => return Err(RebalanceError::new(orders, error)),
+            }
+        }
+        for order in orders.iter_mut() {
+            match waiter.wait_until_terminal(&order.id, self.sell_timeout) {
+                Ok(history) => *order = history.order,
+                Err(WaitError::Timeout(history) | WaitError::NotFilled(history)) => {
+                    *order = history.order
+                }
+                Err(WaitError::Request(_)) => {}
+            }
+        }
+
+        let parse = |value: Option<&str>| value.and_then(|value| value.parse::<f64>().ok());
+        let proceeds: f64 = orders
+            .iter()
+            .map(|order| {
+                parse(order.filled_qty.as_deref()).unwrap_or_default()
+                    * parse(order.filled_avg_price.as_deref()).unwrap_or_default()
+            })
+            .sum();
+        for order in self.funded_buys(plan, proceeds) {
+            match order.send(account_type) {
+                Ok(sent) => orders.push(sent),
+                Err(error) => return Err(RebalanceError::new(orders, error)),
+            }
+        }
+
+        Ok(orders)
+    }
+
+    /// Plan against the live account and execute the plan, or only plan it when
+    /// `dry_run` is set
+    pub fn rebalance(
+        &self,
+        account_type: AccountType,
+        dry_run: bool,
+    ) -> Result<(RebalancePlan, Vec<Order>), RebalanceError> {
+        let plan = self.fetch_plan(account_type)?;
+        if dry_run {
+            return Ok((plan, Vec::new()));
+        }
+        let orders = self.execute(&plan, account_type)?;
+        Ok((plan, orders))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(symbol: &str, qty: f64, price: f64, fractionable: bool) -> Holding {
+        Holding {
+            symbol: symbol.to_string(),
+            qty,
+            price,
+            fractionable,
+        }
+    }
+
+    fn targets(weights: &[(&str, f64)]) -> HashMap<String, f64> {
+        weights
+            .iter()
+            .map(|(symbol, weight)| (symbol.to_string(), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_sells_before_buys() {
+        let rebalancer = Rebalancer::new(targets(&[("AAPL", 0.5), ("MSFT", 0.5)]));
+        let holdings = vec![
+            holding("AAPL", 10.0, 100.0, true),
+            holding("MSFT", 0.0, 50.0, false),
+            holding("TSLA", 5.0, 200.0, true),
+        ];
+
+        // Equity 2000 split 1000 each, TSLA sold in full
+        let plan = rebalancer.plan(0.0, &holdings).unwrap();
+        let orders: Vec<(&str, OrderSide, f64)> = plan
+            .orders
+            .iter()
+            .map(|o| (o.symbol.as_str(), o.side.clone(), o.qty))
+            .collect();
+        assert_eq!(
+            orders,
+            vec![
+                ("TSLA", OrderSide::Sell, 5.0),
+                ("MSFT", OrderSide::Buy, 20.0)
+            ]
+        );
+        assert_eq!(plan.equity, 2000.0);
+        assert_eq!(plan.cash_after, 0.0);
+    }
+
+    #[test]
+    fn test_plan_respects_buffer_and_whole_shares() {
+        let rebalancer = Rebalancer::new(targets(&[("AAPL", 0.6), ("MSFT", 0.3)]))
+            .cash_buffer(0.1)
+            .min_notional(5.0);
+        let holdings = vec![
+            holding("AAPL", 0.0, 30.0, false),
+            holding("MSFT", 0.0, 7.0, true),
+        ];
+
+        let plan = rebalancer.plan(1000.0, &holdings).unwrap();
+        let aapl = plan.buys().find(|o| o.symbol == "AAPL").unwrap();
+        let msft = plan.buys().find(|o| o.symbol == "MSFT").unwrap();
+        assert_eq!(aapl.qty, 18.0);
+        assert!((msft.notional() - 270.0).abs() < 1e-6);
+        assert!(plan.cash_after >= 100.0);
+
+        // Already on target, nothing to trade
+        let holdings = vec![
+            holding("AAPL", 18.0, 30.0, false),
+            holding("MSFT", 270.0 / 7.0, 7.0, true),
+        ];
+        assert!(rebalancer.plan(190.0, &holdings).unwrap().orders.is_empty());
+    }
+
+    #[test]
+    fn test_buys_fit_received_proceeds() {
+        let rebalancer = Rebalancer::new(targets(&[("AAPL", 0.5), ("MSFT", 0.5)]));
+        let holdings = vec![
+            holding("AAPL", 10.0, 100.0, true),
+            holding("MSFT", 0.0, 50.0, false),
+            holding("TSLA", 5.0, 200.0, true),
+        ];
+        let plan = rebalancer.plan(0.0, &holdings).unwrap();
+        let qty = |proceeds| -> Vec<f64> {
+            rebalancer
+                .funded_buys(&plan, proceeds)
+                .iter()
+                .map(|order| order.qty)
+                .collect()
+        };
+
+        assert_eq!(qty(1000.0), vec![20.0]);
+        // Only 3 of the 5 TSLA shares sold
+        assert_eq!(qty(600.0), vec![12.0]);
+        assert!(qty(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_plan_rejects_bad_targets() {
+        let holdings = vec![holding("AAPL", 0.0, 100.0, true)];
+        let overweight = Rebalancer::new(targets(&[("AAPL", 0.8)])).cash_buffer(0.3);
+        assert!(overweight.plan(100.0, &holdings).is_err());
+
+        let unpriced = Rebalancer::new(targets(&[("MSFT", 0.5)]));
+        assert!(unpriced.plan(100.0, &holdings).is_err());
+    }
+}
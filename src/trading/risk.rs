@@ -0,0 +1,533 @@
+//! Risk guardrails checked before orders are submitted or replaced.
+
+use super::{
+    account::{get_account, Account},
+    order::{
+        delete_all_orders, CreateOrderQuery, DeleteOrderResult, GetOrdersQuery, Order,
+        ReplaceOrderQuery,
+    },
+    positions::{AllClosedPositions, Position, PositionsQuery},
+    AccountType,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Reasons the risk manager refused an order
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskViolation {
+    KillSwitchEngaged,
+    SymbolDenied(String),
+    SymbolNotAllowed(String),
+    MaxOrderNotional { notional: f64, limit: f64 },
+    MaxPositionNotional { notional: f64, limit: f64 },
+    MaxGrossExposure { exposure: f64, limit: f64 },
+    DailyLossLimit { loss: f64, limit: f64 },
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskViolation::KillSwitchEngaged => write!(f, "Kill switch is engaged"),
+            RiskViolation::SymbolDenied(symbol) => write!(f, "{symbol} is on the deny list"),
+            RiskViolation::SymbolNotAllowed(symbol) => {
+                write!(f, "{symbol} is not on the allow list")
+            }
+            RiskViolation::MaxOrderNotional { notional, limit } => {
+                write!(f, "Order notional {notional:.2} exceeds {limit:.2}")
+            }
+            RiskViolation::MaxPositionNotional { notional, limit } => {
+                write!(f, "Position notional {notional:.2} would exceed {limit:.2}")
+            }
+            RiskViolation::MaxGrossExposure { exposure, limit } => {
+                write!(f, "Gross exposure {exposure:.2} would exceed {limit:.2}")
+            }
+            RiskViolation::DailyLossLimit { loss, limit } => {
+                write!(f, "Daily loss {loss:.2} has reached {limit:.2}")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RiskError {
+    Rejected(Vec<RiskViolation>),
+    Request(Box<ureq::Error>),
+}
+
+impl From<ureq::Error> for RiskError {
+    fn from(error: ureq::Error) -> Self {
+        RiskError::Request(Box::new(error))
+    }
+}
+
+impl fmt::Display for RiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskError::Rejected(violations) => {
+                let reasons: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                write!(f, "Order rejected: {}", reasons.join(", "))
+            }
+            RiskError::Request(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for RiskError {}
+
+/// An order reduced to what the risk checks need
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedOrder {
+    pub symbol: String,
+    /// Positive for buys and negative for sells
+    pub qty: f64,
+    pub price: f64,
+}
+
+impl ProposedOrder {
+    pub fn new(symbol: &str, qty: f64, price: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            qty,
+            price,
+        }
+    }
+
+    /// The limit or stop price is used when set, otherwise `reference_price`,
+    /// which also converts notional orders into a quantity
+    pub fn from_query(order: &CreateOrderQuery, reference_price: f64) -> Self {
+        let parse = |value: Option<&str>| value.and_then(|value| value.parse::<f64>().ok());
+        let price = parse(order.limit_price)
+            .or(parse(order.stop_price))
+            .unwrap_or(reference_price);
+        let qty = parse(order.qty)
+            .or(parse(order.notional).map(|notional| notional / price))
+            .unwrap_or_default();
+        let sign = if order.side == "sell" { -1.0 } else { 1.0 };
+
+        Self::new(order.symbol, qty * sign, price)
+    }
+
+    /// A replacement of `original` with any quantity or price it changes. Only the
+    /// quantity left to fill is proposed, shares already filled are in the position.
+    pub fn from_replace(
+        replace: &ReplaceOrderQuery,
+        original: &Order,
+        reference_price: f64,
+    ) -> Self {
+        let parse = |value: Option<&str>| value.and_then(|value| value.parse::<f64>().ok());
+        let price = parse(replace.limit_price)
+            .or(parse(original.limit_price.as_deref()))
+            .or(parse(replace.stop_price))
+            .or(parse(original.stop_price.as_deref()))
+            .unwrap_or(reference_price);
+        let qty = parse(replace.qty)
+            .or(parse(original.qty.as_deref()))
+            .unwrap_or_default();
+        let filled_qty = parse(original.filled_qty.as_deref()).unwrap_or_default();
+        let qty = (qty - filled_qty).max(0.0);
+        let sign = if original.side == "sell" { -1.0 } else { 1.0 };
+
+        Self::new(&original.symbol, qty * sign, price)
+    }
+
+    pub fn notional(&self) -> f64 {
+        (self.qty * self.price).abs()
+    }
+}
+
+/// Signed quantity and market value held in a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Exposure {
+    pub qty: f64,
+    pub market_value: f64,
+}
+
+/// Account and position snapshot the risk checks run against
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RiskState {
+    pub equity: f64,
+    pub last_equity: f64,
+    pub positions: HashMap<String, Exposure>,
+}
+
+impl RiskState {
+    pub fn new(account: &Account, positions: &[Position]) -> Self {
+        let parse = |value: &str| value.parse::<f64>().unwrap_or_default();
+        Self {
+            equity: parse(&account.equity),
+            last_equity: parse(&account.last_equity),
+            positions: positions
+                .iter()
+                .map(|position| {
+                    let exposure = Exposure {
+                        qty: parse(&position.qty),
+                        market_value: parse(&position.market_value),
+                    };
+                    (position.symbol.clone(), exposure)
+                })
+                .collect(),
+        }
+    }
+
+    /// Loss since the previous close, zero when the account is up
+    pub fn daily_loss(&self) -> f64 {
+        (self.last_equity - self.equity).max(0.0)
+    }
+
+    pub fn gross_exposure(&self) -> f64 {
+        self.positions
+            .values()
+            .map(|exposure| exposure.market_value.abs())
+            .sum()
+    }
+}
+
+/// Configurable limits enforced in front of order submission. Every limit is off
+/// until set, and orders that only reduce a position skip the size and loss limits.
+pub struct RiskManager {
+    account_type: AccountType,
+    max_order_notional: Option<f64>,
+    max_position_notional: Option<f64>,
+    max_gross_exposure: Option<f64>,
+    max_daily_loss: Option<f64>,
+    allowed_symbols: Option<HashSet<String>>,
+    denied_symbols: HashSet<String>,
+    kill_switch: AtomicBool,
+}
+
+impl RiskManager {
+    pub fn new(account_type: AccountType) -> Self {
+        Self {
+            account_type,
+            max_order_notional: None,
+            max_position_notional: None,
+            max_gross_exposure: None,
+            max_daily_loss: None,
+            allowed_symbols: None,
+            denied_symbols: HashSet::new(),
+            kill_switch: AtomicBool::new(false),
+        }
+    }
+
+    pub fn max_order_notional(mut self, max_order_notional: f64) -> Self {
+        self.max_order_notional = Some(max_order_notional);
+        self
+    }
+
+    /// Largest market value held in any one symbol
+    pub fn max_position_notional(mut self, max_position_notional: f64) -> Self {
+        self.max_position_notional = Some(max_position_notional);
+        self
+    }
+
+    /// Largest sum of absolute market values across all positions
+    pub fn max_gross_exposure(mut self, max_gross_exposure: f64) -> Self {
+        self.max_gross_exposure = Some(max_gross_exposure);
+        self
+    }
+
+    /// Stop adding risk once equity is this far below the previous close
+    pub fn max_daily_loss(mut self, max_daily_loss: f64) -> Self {
+        self.max_daily_loss = Some(max_daily_loss);
+        self
+    }
+
+    /// Only these symbols may be traded
+    pub fn allow_symbols(mut self, symbols: Vec<&str>) -> Self {
+        self.allowed_symbols = Some(symbols.into_iter().map(String::from).collect());
+        self
+    }
+
+    pub fn deny_symbols(mut self, symbols: Vec<&str>) -> Self {
+        self.denied_symbols = symbols.into_iter().map(String::from).collect();
+        self
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.kill_switch.load(Ordering::SeqCst)
+    }
+
+    /// Refuse every further order, cancel all open orders and flatten all positions
+    pub fn engage_kill_switch(
+        &self,
+    ) -> Result<(Vec<DeleteOrderResult>, AllClosedPositions), ureq::Error> {
+        self.kill_switch.store(true, Ordering::SeqCst);
+        let canceled = delete_all_orders(self.account_type)?;
+        let closed = PositionsQuery::new(self.account_type).close_all_positions(true)?;
+
+        Ok((canceled, closed))
+    }
+
+    pub fn reset_kill_switch(&self) {
+        self.kill_switch.store(false, Ordering::SeqCst);
+    }
+
+    /// Run every check against a snapshot, returning all violations found
+    pub fn check(
+        &self,
+        order: &ProposedOrder,
+        state: &RiskState,
+    ) -> Result<(), Vec<RiskViolation>> {
+        let mut violations = Vec::new();
+
+        if self.is_killed() {
+            violations.push(RiskViolation::KillSwitchEngaged);
+        }
+        if self.denied_symbols.contains(&order.symbol) {
+            violations.push(RiskViolation::SymbolDenied(order.symbol.clone()));
+        }
+        if let Some(allowed) = &self.allowed_symbols {
+            if !allowed.contains(&order.symbol) {
+                violations.push(RiskViolation::SymbolNotAllowed(order.symbol.clone()));
+            }
+        }
+
+        let current = state
+            .positions
+            .get(&order.symbol)
+            .copied()
+            .unwrap_or_default();
+        let qty = current.qty + order.qty;
+        if qty.abs() > current.qty.abs() {
+            if let Some(limit) = self.max_order_notional {
+                if order.notional() > limit {
+                    violations.push(RiskViolation::MaxOrderNotional {
+                        notional: order.notional(),
+                        limit,
+                    });
+                }
+            }
+            let position_notional = (qty * order.price).abs();
+            if let Some(limit) = self.max_position_notional {
+                if position_notional > limit {
+                    violations.push(RiskViolation::MaxPositionNotional {
+                        notional: position_notional,
+                        limit,
+                    });
+                }
+            }
+            if let Some(limit) = self.max_gross_exposure {
+                let exposure =
+                    state.gross_exposure() - current.market_value.abs() + position_notional;
+                if exposure > limit {
+                    violations.push(RiskViolation::MaxGrossExposure { exposure, limit });
+                }
+            }
+            if let Some(limit) = self.max_daily_loss {
+                let loss = state.daily_loss();
+                if loss >= limit {
+                    violations.push(RiskViolation::DailyLossLimit { loss, limit });
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Fetch the current account and positions to check orders against
+    pub fn state(&self) -> Result<RiskState, ureq::Error> {
+        let account = get_account(self.account_type)?;
+        let positions = PositionsQuery::new(self.account_type).get_all_open_positions()?;
+
+        Ok(RiskState::new(&account, &positions))
+    }
+
+    /// Check an order against the live account, then submit it.
+    /// `reference_price` prices market orders, see `ProposedOrder::from_query`.
+    pub fn send(&self, order: CreateOrderQuery, reference_price: f64) -> Result<Order, RiskError> {
+        let proposed = ProposedOrder::from_query(&order, reference_price);
+        self.check(&proposed, &self.state()?)
+            .map_err(RiskError::Rejected)?;
+
+        Ok(order.send(self.account_type)?)
+    }
+
+    /// Check the order a replacement would leave open, then submit the replacement
+    pub fn replace(
+        &self,
+        replace: ReplaceOrderQuery,
+        reference_price: f64,
+    ) -> Result<Order, RiskError> {
+        let original = GetOrdersQuery::new(self.account_type).get_by_id(replace.order_id, false)?;
+        let proposed = ProposedOrder::from_replace(&replace, &original, reference_price);
+        self.check(&proposed, &self.state()?)
+            .map_err(RiskError::Rejected)?;
+
+        Ok(replace.send(self.account_type)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::order::{test_order, OrderSide, OrderType, TimeInForce};
+    use serde_json::json;
+
+    fn state() -> RiskState {
+        let mut positions = HashMap::new();
+        positions.insert(
+            "AAPL".to_string(),
+            Exposure {
+                qty: 10.0,
+                market_value: 2000.0,
+            },
+        );
+        positions.insert(
+            "TSLA".to_string(),
+            Exposure {
+                qty: -5.0,
+                market_value: -1000.0,
+            },
+        );
+        RiskState {
+            equity: 9500.0,
+            last_equity: 10000.0,
+            positions,
+        }
+    }
+
+    #[test]
+    fn test_proposed_order_from_query() {
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Sell, OrderType::Limit, TimeInForce::Day)
+                .qty("3")
+                .limit_price("210");
+        assert_eq!(
+            ProposedOrder::from_query(&order, 200.0),
+            ProposedOrder::new("AAPL", -3.0, 210.0)
+        );
+
+        let order =
+            CreateOrderQuery::new("AAPL", OrderSide::Buy, OrderType::Market, TimeInForce::Day)
+                .notional("500");
+        assert_eq!(
+            ProposedOrder::from_query(&order, 200.0),
+            ProposedOrder::new("AAPL", 2.5, 200.0)
+        );
+    }
+
+    #[test]
+    fn test_proposed_order_from_replace() {
+        let original = test_order(
+            "1",
+            "partially_filled",
+            json!({ "side": "sell", "filled_qty": "4", "limit_price": "210" }),
+        );
+
+        let replace = ReplaceOrderQuery::new("1").qty("12");
+        assert_eq!(
+            ProposedOrder::from_replace(&replace, &original, 200.0),
+            ProposedOrder::new("AAPL", -8.0, 210.0)
+        );
+
+        let replace = ReplaceOrderQuery::new("1").limit_price("205");
+        assert_eq!(
+            ProposedOrder::from_replace(&replace, &original, 200.0),
+            ProposedOrder::new("AAPL", -6.0, 205.0)
+        );
+    }
+
+    #[test]
+    fn test_symbol_lists_and_kill_switch() {
+        let manager = RiskManager::new(AccountType::Paper)
+            .allow_symbols(vec!["AAPL", "TSLA"])
+            .deny_symbols(vec!["TSLA"]);
+        let state = state();
+
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", 1.0, 200.0), &state),
+            Ok(())
+        );
+        assert_eq!(
+            manager.check(&ProposedOrder::new("TSLA", 1.0, 200.0), &state),
+            Err(vec![RiskViolation::SymbolDenied("TSLA".to_string())])
+        );
+        assert_eq!(
+            manager.check(&ProposedOrder::new("MSFT", 1.0, 400.0), &state),
+            Err(vec![RiskViolation::SymbolNotAllowed("MSFT".to_string())])
+        );
+
+        manager.kill_switch.store(true, Ordering::SeqCst);
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", 1.0, 200.0), &state),
+            Err(vec![RiskViolation::KillSwitchEngaged])
+        );
+        manager.reset_kill_switch();
+        assert!(!manager.is_killed());
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let manager = RiskManager::new(AccountType::Paper)
+            .max_order_notional(1000.0)
+            .max_position_notional(2500.0)
+            .max_gross_exposure(3500.0);
+        let state = state();
+
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", 2.0, 200.0), &state),
+            Ok(())
+        );
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", 5.0, 200.0), &state),
+            Err(vec![
+                RiskViolation::MaxPositionNotional {
+                    notional: 3000.0,
+                    limit: 2500.0
+                },
+                RiskViolation::MaxGrossExposure {
+                    exposure: 4000.0,
+                    limit: 3500.0
+                }
+            ])
+        );
+        assert_eq!(
+            manager.check(&ProposedOrder::new("MSFT", 3.0, 400.0), &state),
+            Err(vec![
+                RiskViolation::MaxOrderNotional {
+                    notional: 1200.0,
+                    limit: 1000.0
+                },
+                RiskViolation::MaxGrossExposure {
+                    exposure: 4200.0,
+                    limit: 3500.0
+                }
+            ])
+        );
+        // Covering a short only reduces risk
+        assert_eq!(
+            manager.check(&ProposedOrder::new("TSLA", 5.0, 200.0), &state),
+            Ok(())
+        );
+        // Closing a position larger than the order limit is still allowed
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", -10.0, 200.0), &state),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_daily_loss_limit() {
+        let manager = RiskManager::new(AccountType::Paper).max_daily_loss(500.0);
+        let state = state();
+
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", 1.0, 200.0), &state),
+            Err(vec![RiskViolation::DailyLossLimit {
+                loss: 500.0,
+                limit: 500.0
+            }])
+        );
+        assert_eq!(
+            manager.check(&ProposedOrder::new("AAPL", -10.0, 200.0), &state),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use super::{assets::Asset, AccountType};
+use crate::request;
+use serde::{Deserialize, Serialize};
+use ureq::Request;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Watchlist {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Only returned when getting, creating or changing a single watchlist
+    pub assets: Option<Vec<Asset>>,
+}
+
+pub type AllWatchlists = Vec<Watchlist>;
+
+/// How to address a watchlist, by its id or by its unique name
+#[derive(Debug, Clone, Copy)]
+pub enum WatchlistKey<'a> {
+    Id(&'a str),
+    Name(&'a str),
+}
+
+#[derive(Serialize, Debug)]
+struct WatchlistBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbols: Option<Vec<&'a str>>,
+}
+
+#[derive(Serialize, Debug)]
+struct SymbolBody<'a> {
+    symbol: &'a str,
+}
+
+pub struct WatchlistsQuery<'a> {
+    url: &'a str,
+}
+
+impl<'a> WatchlistsQuery<'a> {
+    pub fn new(account_type: AccountType) -> Self {
+        Self {
+            url: match account_type {
+                AccountType::Live => "https://api.alpaca.markets/v2/watchlists",
+                AccountType::Paper => "https://paper-api.alpaca.markets/v2/watchlists",
+            },
+        }
+    }
+
+    /// Route for a watchlist, with an optional trailing path such as a symbol
+    fn route(&self, key: WatchlistKey, path: Option<&str>) -> String {
+        let path = path.map(|path| format!("/{}", path)).unwrap_or_default();
+        match key {
+            WatchlistKey::Id(id) => format!("{}/{}{}", self.url, id, path),
+            WatchlistKey::Name(_) => format!("{}:by_name{}", self.url, path),
+        }
+    }
+
+    fn request(&self, method: &str, key: WatchlistKey, path: Option<&str>) -> Request {
+        let request = request(method, &self.route(key, path));
+        match key {
+            WatchlistKey::Id(_) => request,
+            WatchlistKey::Name(name) => request.query("name", name),
+        }
+    }
+
+    pub fn get_all(&self) -> Result<AllWatchlists, ureq::Error> {
+        let response = request("GET", self.url).call()?;
+        let watchlists = response.into_json()?;
+
+        Ok(watchlists)
+    }
+
+    pub fn create(&self, name: &'a str, symbols: Vec<&'a str>) -> Result<Watchlist, ureq::Error> {
+        let body = WatchlistBody {
+            name: Some(name),
+            symbols: Some(symbols),
+        };
+        let response = request("POST", self.url).send_json(&body)?;
+        let watchlist = response.into_json()?;
+
+        Ok(watchlist)
+    }
+
+    pub fn get(&self, key: WatchlistKey) -> Result<Watchlist, ureq::Error> {
+        let response = self.request("GET", key, None).call()?;
+        let watchlist = response.into_json()?;
+
+        Ok(watchlist)
+    }
+
+    pub fn get_by_id(&self, id: &'a str) -> Result<Watchlist, ureq::Error> {
+        self.get(WatchlistKey::Id(id))
+    }
+
+    pub fn get_by_name(&self, name: &'a str) -> Result<Watchlist, ureq::Error> {
+        self.get(WatchlistKey::Name(name))
+    }
+
+    /// Rename a watchlist and or replace its symbols, `None` leaves that part unchanged
+    pub fn update(
+        &self,
+        key: WatchlistKey,
+        name: Option<&'a str>,
+        symbols: Option<Vec<&'a str>>,
+    ) -> Result<Watchlist, ureq::Error> {
+        let body = WatchlistBody { name, symbols };
+        let response = self.request("PUT", key, None).send_json(&body)?;
+        let watchlist = response.into_json()?;
+
+        Ok(watchlist)
+    }
+
+    pub fn add_symbol(&self, key: WatchlistKey, symbol: &'a str) -> Result<Watchlist, ureq::Error> {
+        let response = self
+            .request("POST", key, None)
+            .send_json(&SymbolBody { symbol })?;
+        let watchlist = response.into_json()?;
+
+        Ok(watchlist)
+    }
+
+    pub fn remove_symbol(
+        &self,
+        key: WatchlistKey,
+        symbol: &'a str,
+    ) -> Result<Watchlist, ureq::Error> {
+        let response = self.request("DELETE", key, Some(symbol)).call()?;
+        let watchlist = response.into_json()?;
+
+        Ok(watchlist)
+    }
+
+    pub fn delete(&self, key: WatchlistKey) -> Result<(), ureq::Error> {
+        self.request("DELETE", key, None).call()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchlist_routes() {
+        let query = WatchlistsQuery::new(AccountType::Paper);
+
+        assert_eq!(
+            query.route(WatchlistKey::Id("abc"), None),
+            "https://paper-api.alpaca.markets/v2/watchlists/abc"
+        );
+        assert_eq!(
+            query.route(WatchlistKey::Id("abc"), Some("AAPL")),
+            "https://paper-api.alpaca.markets/v2/watchlists/abc/AAPL"
+        );
+        assert_eq!(
+            query.route(WatchlistKey::Name("Tech"), Some("AAPL")),
+            "https://paper-api.alpaca.markets/v2/watchlists:by_name/AAPL"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_watchlist() {
+        let watchlist: Watchlist = serde_json::from_str(
+            r#"{
+                "id": "3174d6df-7726-44b4-a5bd-7fda5ae6e009",
+                "account_id": "abe25343-a7ba-4255-bdeb-f7e013e9ee5d",
+                "name": "Tech",
+                "created_at": "2024-09-20T14:28:58.123Z",
+                "updated_at": "2024-09-20T14:28:58.123Z",
+                "assets": [{
+                    "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+                    "class": "us_equity",
+                    "exchange": "NASDAQ",
+                    "symbol": "AAPL",
+                    "name": "Apple Inc. Common Stock",
+                    "status": "active",
+                    "tradable": true,
+                    "marginable": true,
+                    "shortable": true,
+                    "easy_to_borrow": true,
+                    "fractionable": true
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(watchlist.name, "Tech");
+        assert_eq!(watchlist.assets.unwrap()[0].symbol, "AAPL");
+    }
+
+    #[test]
+    fn test_get_all_watchlists() {
+        let res = WatchlistsQuery::new(AccountType::Paper).get_all().unwrap();
+        dbg!(&res);
+    }
+}